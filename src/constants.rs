@@ -11,6 +11,7 @@ impl Memory {
     pub const STATUS: u8 = 0x07;
     pub const OBSERVE_TX: u8 = 0x08;
     pub const CD: u8 = 0x09;
+    pub const RPD: u8 = 0x09;
     pub const RX_ADDR_P0: u8 = 0x0A;
     pub const RX_ADDR_P1: u8 = 0x0B;
     pub const RX_ADDR_P2: u8 = 0x0C;
@@ -56,6 +57,7 @@ impl BitMnemonic {
     pub const ARC: u8 = 0;
     pub const PLL_LOCK: u8 = 4;
     pub const RF_DR: u8 = 3;
+    pub const RF_DR_LOW: u8 = 5;
     pub const RF_PWR: u8 = 1;
     pub const LNA_HCURR: u8 = 0;
     pub const RX_DR: u8 = 6;
@@ -72,7 +74,13 @@ impl BitMnemonic {
     pub const RX_EMPTY: u8 = 0;
     pub const DPL_P0: u8 = 0;
     pub const DPL_P1: u8 = 1;
+    pub const DPL_P2: u8 = 2;
+    pub const DPL_P3: u8 = 3;
+    pub const DPL_P4: u8 = 4;
+    pub const DPL_P5: u8 = 5;
     pub const EN_DPL: u8 = 2;
+    pub const EN_ACK_PAY: u8 = 1;
+    pub const EN_DYN_ACK: u8 = 0;
 }
 
 pub struct Instruction {}
@@ -87,8 +95,13 @@ impl Instruction {
     pub const FLUSH_RX: u8 = 0xE2;
     pub const REUSE_TX_PL: u8 = 0xE3;
     pub const R_RX_PL_WID: u8 = 0x60;
+    pub const ACTIVATE: u8 = 0x50;
+    pub const W_ACK_PAYLOAD: u8 = 0xA8;
+    pub const W_TX_PAYLOAD_NOACK: u8 = 0xB0;
     pub const NOP: u8 = 0xFF;
 }
 
-pub const MIRF_CONFIG: u8 = ((1 << 3) | (1 << 2));
+pub const MIRF_CONFIG: u8 = (1 << 3) | (1 << 2);
 pub const MIRF_ADDR_LEN: u8 = 5;
+pub const MAX_PAYLOAD: usize = 32;
+pub const MAX_CHANNEL: u8 = 125;
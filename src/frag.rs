@@ -0,0 +1,99 @@
+//! Fragmentation of messages that don't fit into a single payload
+//!
+//! Every fragment starts with a two byte header (fragment index, fragment count) followed by up
+//! to `FRAGMENT_DATA_LEN` bytes of the message. Fragments are sent with dynamic payloads, so
+//! the receiver knows the length of the last one.
+
+use embedded_hal::blocking;
+use embedded_hal::blocking::delay::DelayUs;
+use embedded_hal::digital::v2::OutputPin;
+
+use crate::{payload_buffer, Error, SendOutcome, MAX_PAYLOAD, NRF24L01};
+
+const FRAGMENT_HEADER_LEN: usize = 2;
+const FRAGMENT_DATA_LEN: usize = MAX_PAYLOAD - FRAGMENT_HEADER_LEN;
+
+impl<E, SPI, CSN, CE, DELAY, IRQ> NRF24L01<SPI, CSN, CE, DELAY, IRQ>
+where
+    SPI: blocking::spi::Transfer<u8, Error = E> + blocking::spi::Write<u8, Error = E>,
+    CSN: OutputPin,
+    CE: OutputPin,
+    DELAY: DelayUs<u16>,
+{
+    /// Send `data` as a sequence of fragments, waiting for each to be acknowledged.
+    ///
+    /// Requires dynamic payloads (a payload size of 0) and at most 255 fragments. A fragment
+    /// hitting MAX_RT aborts the message with `Error::MaxRetries`.
+    pub fn send_message(&mut self, data: &[u8]) -> Result<(), Error<E>> {
+        let total = data.len().div_ceil(FRAGMENT_DATA_LEN).max(1);
+        if !self.using_dynamic_payload() || total > u8::MAX as usize {
+            return Err(Error::InvalidArgument);
+        }
+
+        let mut fragment = payload_buffer();
+        for index in 0..total {
+            let start = index * FRAGMENT_DATA_LEN;
+            let end = (start + FRAGMENT_DATA_LEN).min(data.len());
+            let chunk = &data[start..end];
+
+            fragment[0] = index as u8;
+            fragment[1] = total as u8;
+            fragment[FRAGMENT_HEADER_LEN..FRAGMENT_HEADER_LEN + chunk.len()].copy_from_slice(chunk);
+            let outcome =
+                self.transmit_then_listen(&fragment[..FRAGMENT_HEADER_LEN + chunk.len()])?;
+            if outcome == SendOutcome::MaxRetries {
+                return Err(Error::MaxRetries);
+            }
+        }
+        Ok(())
+    }
+
+    /// Receive a message sent with `send_message` into `buf`, returning its length.
+    ///
+    /// Blocks until every fragment has arrived. A fragment received out of order yields
+    /// `Error::MissingFragment`, unless it starts a new message, which then replaces the broken
+    /// one. The next call discards what is left of a broken message until a fragment with
+    /// index 0 arrives.
+    pub fn recv_message(&mut self, buf: &mut [u8]) -> Result<usize, Error<E>> {
+        let mut fragment = payload_buffer();
+        let mut expected = 0;
+        let mut total = 0;
+        let mut length = 0;
+
+        loop {
+            while !self.data_ready()? {}
+            let received = self.get_data(&mut fragment)? as usize;
+            if received < FRAGMENT_HEADER_LEN {
+                return Err(Error::MissingFragment);
+            }
+
+            let (index, count) = (fragment[0], fragment[1]);
+            if index == 0 {
+                // A new message replaces one that is still incomplete
+                expected = 0;
+                length = 0;
+            } else if expected == 0 {
+                // Left over from a broken message
+                continue;
+            }
+            if index != expected || count == 0 || (expected > 0 && count != total) {
+                return Err(Error::MissingFragment);
+            }
+            total = count;
+
+            let chunk = &fragment[FRAGMENT_HEADER_LEN..received];
+            if length + chunk.len() > buf.len() {
+                return Err(Error::BufferTooSmall {
+                    needed: length + chunk.len(),
+                });
+            }
+            buf[length..length + chunk.len()].copy_from_slice(chunk);
+            length += chunk.len();
+
+            expected += 1;
+            if expected == total {
+                return Ok(length);
+            }
+        }
+    }
+}
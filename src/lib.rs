@@ -8,20 +8,81 @@
 #![no_std]
 
 extern crate embedded_hal;
+#[cfg(test)]
+extern crate std;
 
 use embedded_hal::blocking;
-use embedded_hal::digital::v2::OutputPin;
+use embedded_hal::blocking::delay::DelayUs;
+use embedded_hal::digital::v2::{InputPin, OutputPin};
 use embedded_hal::spi::{Mode, Phase, Polarity};
 
+use crate::timing::{
+    ard_steps_for_us, ard_steps_to_us, min_ard_steps, CE_PULSE_US, RETRY_BACKOFF_US,
+    RPD_SAMPLE_INTERVAL_US, STATUS_POLL_US, TPD2STBY_US, TSTBY2A_US,
+};
+
 mod constants;
-pub use crate::constants::{BitMnemonic, Instruction, Memory, MIRF_ADDR_LEN, MIRF_CONFIG};
+#[cfg(feature = "frag")]
+mod frag;
+#[cfg(test)]
+mod mock;
+mod provision;
+mod rx;
+mod status;
+mod timing;
+pub use crate::constants::{
+    BitMnemonic, Instruction, Memory, MAX_CHANNEL, MAX_PAYLOAD, MIRF_ADDR_LEN, MIRF_CONFIG,
+};
+pub use crate::provision::Nrf24Config;
+pub use crate::rx::{Payload, RxIter};
+pub use crate::status::{FifoOccupancy, FifoStatus, Status};
+pub use crate::timing::{airtime_us, min_rx_window_us};
 
-/// SPI mode
+/// SPI mode the radio needs: CPOL = 0, CPHA = 0 (mode 0), MSB first, at most 10MHz.
+///
+/// The driver can't change the bus mode through the embedded-hal 0.2 traits. On a bus shared
+/// with devices using another mode, whatever arbitrates the bus must switch back to `MODE`
+/// before every driver call, or the other device's code must restore it, see `with_spi`.
 pub const MODE: Mode = Mode {
     phase: Phase::CaptureOnFirstTransition,
     polarity: Polarity::IdleLow,
 };
 
+/// A zeroed buffer large enough for any payload
+pub fn payload_buffer() -> [u8; MAX_PAYLOAD] {
+    [0; MAX_PAYLOAD]
+}
+
+/// The minimum distance in channels (MHz) between two links at `data_rate`.
+///
+/// At 2Mbps a signal occupies about 2MHz, so channels must be at least 2 apart to not
+/// overlap. 1Mbps and 250kbps fit into 1MHz.
+pub fn min_channel_spacing(data_rate: DataRate) -> u8 {
+    match data_rate {
+        DataRate::R2Mbps => 2,
+        DataRate::R1Mbps | DataRate::R250Kbps => 1,
+    }
+}
+
+/// A `DelayUs` that doesn't wait at all.
+///
+/// Only use this if the datasheet timings are already met some other way, e.g. by a slow SPI
+/// clock. It skips the mandatory 1.5ms crystal start-up after PWR_UP and the 130µs settle
+/// time after raising CE, so on some modules the first packet after powering up is lost.
+///
+/// Everything timed with the delay degrades as well: `estimate_signal` and `link_health`
+/// sample RPD back to back, `ping` returns meaningless times, and the timeout of
+/// `send_blocking_with_space` becomes a count of STATUS polls.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoDelay;
+
+impl DelayUs<u16> for NoDelay {
+    fn delay_us(&mut self, _us: u16) {}
+}
+
+/// Placeholder for the IRQ pin of a driver created with `NRF24L01::new`
+pub struct NoIrq;
+
 /// Error
 #[derive(Debug)]
 pub enum Error<E> {
@@ -31,6 +92,28 @@ pub enum Error<E> {
     Spi(E),
     /// GPIO read/write error
     Gpio,
+    /// An argument was out of range for the requested operation
+    InvalidArgument,
+    /// A fragment of a message was lost or received out of order
+    MissingFragment,
+    /// No acknowledgement arrived within the configured number of retransmits
+    MaxRetries,
+    /// The buffer can't hold the data, `needed` bytes are required
+    BufferTooSmall { needed: usize },
+    /// The chip doesn't respond, MISO reads a STATUS that can't be valid
+    NotConnected,
+    /// Bitmask of enabled pipes with dynamic payloads that can't work, because auto-ack or
+    /// EN_DPL is off
+    MisconfiguredPipes(u8),
+    /// The operation didn't complete within the given timeout
+    Timeout,
+    /// `register` doesn't hold the value the driver expects, e.g. after a brownout reset
+    VerificationFailed { register: u8 },
+    /// A register holds a value the datasheet calls illegal
+    InvalidState,
+    /// The RX FIFO reported a payload wider than `MAX_PAYLOAD`, it was flushed and RX_DR
+    /// cleared
+    CorruptRxFifo,
 }
 
 impl<E> From<E> for Error<E> {
@@ -39,40 +122,480 @@ impl<E> From<E> for Error<E> {
     }
 }
 
-pub struct NRF24L01<SPI, CSN, CE> {
+/// Result of `NRF24L01::self_test`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SelfTestReport {
+    /// The chip answered with a plausible STATUS and SETUP_AW
+    pub present: bool,
+    /// A test pattern written to TX_ADDR was read back unchanged
+    pub spi_integrity: bool,
+    /// RF_CH and RF_SETUP have their reserved bits cleared
+    pub rf_registers: bool,
+}
+
+impl SelfTestReport {
+    /// True if every check passed
+    pub fn passed(&self) -> bool {
+        self.present && self.spi_integrity && self.rf_registers
+    }
+}
+
+/// Data pipe
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pipe {
+    P0,
+    P1,
+    P2,
+    P3,
+    P4,
+    P5,
+}
+
+impl Pipe {
+    /// The pipe with the given number, or `None` if it isn't in 0..=5
+    pub fn new(number: u8) -> Option<Self> {
+        match number {
+            0 => Some(Pipe::P0),
+            1 => Some(Pipe::P1),
+            2 => Some(Pipe::P2),
+            3 => Some(Pipe::P3),
+            4 => Some(Pipe::P4),
+            5 => Some(Pipe::P5),
+            _ => None,
+        }
+    }
+
+    /// The pipe in the RX_P_NO field of a STATUS value, or `None` when it reads 0b111 (RX FIFO
+    /// empty) or the unused 0b110
+    pub fn from_status(status: u8) -> Option<Self> {
+        Pipe::new((status >> BitMnemonic::RX_P_NO) & 0b111)
+    }
+
+    /// The pipe number
+    pub fn number(self) -> u8 {
+        self as u8
+    }
+}
+
+/// RF channel, 0 to `MAX_CHANNEL`, at 2400MHz + channel number
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Channel(u8);
+
+impl Channel {
+    /// The channel with the given number, or `None` if it's above `MAX_CHANNEL`
+    pub fn new(number: u8) -> Option<Self> {
+        if number > MAX_CHANNEL {
+            return None;
+        }
+        Some(Channel(number))
+    }
+
+    /// The channel at `mhz`, or `None` outside 2400 to 2525MHz
+    pub fn from_mhz(mhz: u16) -> Option<Self> {
+        if !(2400..=2400 + MAX_CHANNEL as u16).contains(&mhz) {
+            return None;
+        }
+        Some(Channel((mhz - 2400) as u8))
+    }
+
+    /// The channel frequency in MHz
+    pub fn to_mhz(self) -> u16 {
+        2400 + self.0 as u16
+    }
+
+    /// The channel number
+    pub fn number(self) -> u8 {
+        self.0
+    }
+}
+
+/// An address of `N` bytes, least significant byte first.
+///
+/// Only `MIRF_ADDR_LEN` (5) bytes compile, the width the driver always uses. The chip's 3 and 4
+/// byte widths aren't supported. The address setters take any `AsRef<[u8]>`, so plain slices
+/// still work, but are rejected at runtime unless 5 bytes long.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Address<const N: usize>([u8; N]);
+
+impl<const N: usize> Address<N> {
+    const VALID_WIDTH: () = assert!(N == MIRF_ADDR_LEN as usize, "addresses are 5 bytes wide");
+
+    pub const fn new(bytes: [u8; N]) -> Self {
+        #[allow(clippy::let_unit_value)]
+        let () = Self::VALID_WIDTH;
+        Address(bytes)
+    }
+}
+
+impl<const N: usize> From<[u8; N]> for Address<N> {
+    fn from(bytes: [u8; N]) -> Self {
+        Address::new(bytes)
+    }
+}
+
+impl<const N: usize> AsRef<[u8]> for Address<N> {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+/// Air data rate
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DataRate {
+    R250Kbps,
+    R1Mbps,
+    R2Mbps,
+}
+
+/// Address width
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AddressWidth {
+    Three,
+    Four,
+    Five,
+}
+
+impl AddressWidth {
+    /// The width in bytes
+    pub fn bytes(self) -> u8 {
+        match self {
+            AddressWidth::Three => 3,
+            AddressWidth::Four => 4,
+            AddressWidth::Five => 5,
+        }
+    }
+}
+
+/// CRC encoding scheme
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CrcMode {
+    Disabled,
+    OneByte,
+    TwoBytes,
+}
+
+impl CrcMode {
+    /// The CRC length in bytes
+    pub fn bytes(self) -> u8 {
+        match self {
+            CrcMode::Disabled => 0,
+            CrcMode::OneByte => 1,
+            CrcMode::TwoBytes => 2,
+        }
+    }
+}
+
+/// Interrupt event passed to the `NRF24L01::on_irq` handler
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Event<'a> {
+    /// A packet was received on `pipe`
+    DataReady { pipe: Pipe, data: &'a [u8] },
+    /// A packet was sent (and acknowledged, if auto-ack is enabled)
+    DataSent,
+    /// The maximum number of retransmits was reached without an acknowledgement
+    MaxRetransmit,
+}
+
+/// Primary role selected by PRIM_RX, see `NRF24L01::set_role`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    /// Receiver (PRIM_RX set)
+    PrimaryRx,
+    /// Transmitter (PRIM_RX clear)
+    PrimaryTx,
+}
+
+/// Retransmit counts (ARC_CNT) of the transmissions completed since the last
+/// `NRF24L01::reset_stats`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TxStats {
+    /// Number of completed transmissions, including those ending in MAX_RT
+    pub packets: u32,
+    /// Fewest retransmits of a single packet
+    pub min_retransmits: u8,
+    /// Most retransmits of a single packet
+    pub max_retransmits: u8,
+    /// Retransmits of all packets together
+    pub total_retransmits: u32,
+}
+
+impl TxStats {
+    /// Average retransmits per packet, 0 without any packets
+    pub fn average_retransmits(&self) -> f32 {
+        if self.packets == 0 {
+            return 0.0;
+        }
+        self.total_retransmits as f32 / self.packets as f32
+    }
+
+    fn record(&mut self, retransmits: u8) {
+        if self.packets == 0 {
+            self.min_retransmits = retransmits;
+            self.max_retransmits = retransmits;
+        } else {
+            self.min_retransmits = self.min_retransmits.min(retransmits);
+            self.max_retransmits = self.max_retransmits.max(retransmits);
+        }
+        self.packets = self.packets.saturating_add(1);
+        self.total_retransmits = self.total_retransmits.saturating_add(retransmits as u32);
+    }
+}
+
+/// Rough link quality, see `NRF24L01::link_health`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkHealth {
+    Good,
+    Marginal,
+    Bad,
+}
+
+/// State of a transmission, see `NRF24L01::poll_send`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SendState {
+    /// Nothing is being sent
+    Idle,
+    /// The packet is still on air or waiting for its ACK
+    InProgress,
+    /// The transmission just finished
+    Completed(SendOutcome),
+}
+
+/// Outcome of a completed transmission
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SendOutcome {
+    /// The packet was sent (and acknowledged, if auto-ack is enabled)
+    Sent,
+    /// No acknowledgement arrived within the configured number of retransmits
+    MaxRetries,
+}
+
+/// Die revision of the chip
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChipVariant {
+    /// nRF24L01+, supports 250kbps, RPD and the extended features without ACTIVATE
+    Plus,
+    /// Original nRF24L01
+    NonPlus,
+}
+
+/// How the CE pin is driven
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CeMode {
+    /// CE is driven by the driver through the CE pin
+    SoftwareControlled,
+    /// CE is tied high on the board, the CE pin is never touched
+    TiedHigh,
+}
+
+pub struct NRF24L01<SPI, CSN, CE, DELAY, IRQ = NoIrq> {
     spi: SPI,
     csn: CSN,
     ce: CE,
+    delay: DELAY,
+    irq: IRQ,
 
     channel: u8,
     payload_size: u8,
     tx_power_status: bool,
+    powered_up: bool,
+    feature: u8,
+    config_base: u8,
+    ce_state: bool,
+    ce_mode: CeMode,
+    tx_address: [u8; MIRF_ADDR_LEN as usize],
+    pipe0_address: Option<[u8; MIRF_ADDR_LEN as usize]>,
+    pipe0_holds_tx: bool,
+    last_sequence: Option<u8>,
+    chip_variant: Option<ChipVariant>,
+    tx_only: bool,
+    auto_ard: bool,
+    tx_stats: TxStats,
+    last_status: Status,
 }
 
-impl<E, SPI, CSN, CE> NRF24L01<SPI, CSN, CE>
+impl<E, SPI, CSN, CE, DELAY> NRF24L01<SPI, CSN, CE, DELAY>
 where
     SPI: blocking::spi::Transfer<u8, Error = E> + blocking::spi::Write<u8, Error = E>,
     CSN: OutputPin,
     CE: OutputPin,
+    DELAY: DelayUs<u16>,
 {
+    /// Create a driver, leaving the chip as it is until `config`.
+    ///
+    /// Returns `Error::InvalidArgument` if `channel` is above `MAX_CHANNEL` or `payload_size`
+    /// above `MAX_PAYLOAD`. A `payload_size` of 0 selects dynamic payloads.
     pub fn new(
         spi: SPI,
         csn: CSN,
         ce: CE,
+        delay: DELAY,
+        channel: u8,
+        payload_size: u8,
+    ) -> Result<Self, Error<E>> {
+        Self::init(spi, csn, ce, NoIrq, delay, channel, payload_size)
+    }
+
+    /// Create a driver set up to transmit 32 byte payloads to `address` on `channel`.
+    ///
+    /// Auto-ack is enabled with up to 15 retransmits 500µs apart, and the radio is left in TX
+    /// standby, ready for `send`.
+    pub fn transmitter(
+        spi: SPI,
+        csn: CSN,
+        ce: CE,
+        delay: DELAY,
+        channel: u8,
+        address: &[u8],
+    ) -> Result<Self, Error<E>> {
+        let mut nrf24l01 = Self::basic(spi, csn, ce, delay, channel, address)?;
+        nrf24l01.set_taddr(address)?;
+        nrf24l01.config()?;
+        nrf24l01.standby_tx()?;
+        Ok(nrf24l01)
+    }
+
+    /// Create a driver listening for 32 byte payloads to `address` (on pipe 1) on `channel`,
+    /// with auto-ack enabled.
+    pub fn receiver(
+        spi: SPI,
+        csn: CSN,
+        ce: CE,
+        delay: DELAY,
+        channel: u8,
+        address: &[u8],
+    ) -> Result<Self, Error<E>> {
+        let mut nrf24l01 = Self::basic(spi, csn, ce, delay, channel, address)?;
+        nrf24l01.set_pipe_address(Pipe::P1, address)?;
+        nrf24l01.config()?;
+        Ok(nrf24l01)
+    }
+
+    fn basic(
+        spi: SPI,
+        csn: CSN,
+        ce: CE,
+        delay: DELAY,
+        channel: u8,
+        address: &[u8],
+    ) -> Result<Self, Error<E>> {
+        Self::check_address(address)?;
+
+        let mut nrf24l01 = Self::new(spi, csn, ce, delay, channel, MAX_PAYLOAD as u8)?;
+        nrf24l01.config_register(Memory::EN_AA, &0x3F)?;
+        // 500µs retransmit delay, 15 retransmits
+        nrf24l01.config_register(Memory::SETUP_RETR, &0x1F)?;
+        Ok(nrf24l01)
+    }
+
+    pub fn free(self) -> (SPI, CSN, CE, DELAY) {
+        (self.spi, self.csn, self.ce, self.delay)
+    }
+
+    /// Power the radio down, then return the peripherals.
+    ///
+    /// Unlike `free`, which is infallible and leaves the radio as it is, this talks to the chip
+    /// and can fail, in which case the peripherals are lost along with the driver.
+    pub fn release(mut self) -> Result<(SPI, CSN, CE, DELAY), Error<E>> {
+        self.power_down()?;
+        Ok(self.free())
+    }
+}
+
+impl<E, SPI, CSN, CE, DELAY, IRQ> NRF24L01<SPI, CSN, CE, DELAY, IRQ>
+where
+    SPI: blocking::spi::Transfer<u8, Error = E> + blocking::spi::Write<u8, Error = E>,
+    CSN: OutputPin,
+    CE: OutputPin,
+    DELAY: DelayUs<u16>,
+    IRQ: InputPin,
+{
+    /// Like `new`, but also takes the IRQ pin.
+    ///
+    /// IRQ is active low and open drain, so the pin needs a pull-up unless the module has one.
+    pub fn new_with_irq(
+        spi: SPI,
+        csn: CSN,
+        ce: CE,
+        irq: IRQ,
+        delay: DELAY,
+        channel: u8,
+        payload_size: u8,
+    ) -> Result<Self, Error<E>> {
+        Self::init(spi, csn, ce, irq, delay, channel, payload_size)
+    }
+
+    pub fn free(self) -> (SPI, CSN, CE, DELAY, IRQ) {
+        (self.spi, self.csn, self.ce, self.delay, self.irq)
+    }
+
+    /// Power the radio down, then return the peripherals, see the `release` of drivers
+    /// without an IRQ pin
+    pub fn release(mut self) -> Result<(SPI, CSN, CE, DELAY, IRQ), Error<E>> {
+        self.power_down()?;
+        Ok(self.free())
+    }
+
+    /// True while the IRQ pin is asserted (low)
+    pub fn irq_active(&self) -> Result<bool, Error<E>> {
+        self.irq.is_low().map_err(|_| Error::Gpio)
+    }
+
+    /// Block until the IRQ pin signals that data is ready
+    pub fn wait_for_data(&mut self) -> Result<(), Error<E>> {
+        loop {
+            if self.irq_active()? && self.data_ready()? {
+                return Ok(());
+            }
+        }
+    }
+}
+
+impl<E, SPI, CSN, CE, DELAY, IRQ> NRF24L01<SPI, CSN, CE, DELAY, IRQ>
+where
+    SPI: blocking::spi::Transfer<u8, Error = E> + blocking::spi::Write<u8, Error = E>,
+    CSN: OutputPin,
+    CE: OutputPin,
+    DELAY: DelayUs<u16>,
+{
+    fn init(
+        spi: SPI,
+        csn: CSN,
+        ce: CE,
+        irq: IRQ,
+        delay: DELAY,
         channel: u8,
         payload_size: u8,
     ) -> Result<Self, Error<E>> {
+        if channel > MAX_CHANNEL || payload_size as usize > MAX_PAYLOAD {
+            return Err(Error::InvalidArgument);
+        }
         let mut nrf24l01 = NRF24L01 {
             spi,
             csn,
             ce,
+            delay,
+            irq,
 
             channel,
             payload_size,
             tx_power_status: false,
+            powered_up: false,
+            feature: 0,
+            config_base: MIRF_CONFIG,
+            ce_state: false,
+            ce_mode: CeMode::SoftwareControlled,
+            tx_address: [0; MIRF_ADDR_LEN as usize],
+            pipe0_address: None,
+            pipe0_holds_tx: false,
+            last_sequence: None,
+            chip_variant: None,
+            tx_only: false,
+            auto_ard: true,
+            tx_stats: TxStats::default(),
+            last_status: Status(0),
         };
 
-        nrf24l01.ce.set_low().map_err(|_| Error::Gpio)?;
+        nrf24l01.set_ce_low()?;
         nrf24l01.csn.set_high().map_err(|_| Error::Gpio)?;
 
         Ok(nrf24l01)
@@ -87,189 +610,2311 @@ where
         let channel = self.channel;
         self.config_register(Memory::RF_CH, &channel)?;
 
-        if (self.using_dynamic_payload()) {
+        if self.using_dynamic_payload() {
             // Dynamic payload
-            self.config_register(Memory::FEATURE, &(1 << BitMnemonic::EN_DPL));
+            self.feature |= 1 << BitMnemonic::EN_DPL;
+            self.write_feature()?;
             self.config_register(
                 Memory::DYN_PD,
                 &((1 << BitMnemonic::DPL_P0) | (1 << BitMnemonic::DPL_P1)),
-            );
+            )?;
         } else {
             // Static payload
+            self.write_feature()?;
             let payload_size = self.payload_size;
             self.config_register(Memory::RX_PW_P0, &payload_size)?;
             self.config_register(Memory::RX_PW_P1, &payload_size)?;
         }
 
-        self.power_up_rx()?;
+        if self.tx_only {
+            self.standby_tx()?;
+        } else {
+            self.power_up_rx()?;
+        }
         self.flush_rx()?;
         Ok(())
     }
 
-    fn config_register(&mut self, register: u8, value: &u8) -> Result<(), Error<E>> {
-        self.csn.set_low().map_err(|_| Error::Gpio)?;
-        self.spi
-            .write(&[Instruction::W_REGISTER | (Instruction::REGISTER_MASK & register)])?;
-        self.spi.write(&[*value])?;
-        self.csn.set_high().map_err(|_| Error::Gpio)?;
+    /// Run `config` and start receiving, then read CONFIG back to check PWR_UP and PRIM_RX
+    /// are set, returning `Error::VerificationFailed` otherwise.
+    ///
+    /// Entering RX waits 1.5ms if the radio was powered down, plus the 130µs settle time, so
+    /// the radio listens once this returns. A failed check points at wiring or SPI problems
+    /// right at startup instead of at the first missed packet. Enters RX even with `tx_only`.
+    pub fn begin_receiving(&mut self) -> Result<(), Error<E>> {
+        self.config()?;
+        if self.tx_only {
+            self.power_up_rx()?;
+        }
+        let expected = (1 << BitMnemonic::PWR_UP) | (1 << BitMnemonic::PRIM_RX);
+        if self.read_register(Memory::CONFIG)? & expected != expected {
+            return Err(Error::VerificationFailed {
+                register: Memory::CONFIG,
+            });
+        }
         Ok(())
     }
 
-    fn read_register(&mut self, register: u8) -> Result<u8, Error<E>> {
-        self.csn.set_low().map_err(|_| Error::Gpio)?;
-        self.spi
-            .write(&[Instruction::R_REGISTER | (Instruction::REGISTER_MASK & register)])?;
-        let mut buffer = [0];
-        self.spi.transfer(&mut buffer)?;
-        self.csn.set_high().map_err(|_| Error::Gpio)?;
-        Ok(buffer[0])
+    /// Make this a pure transmitter: `config` leaves the radio in TX standby and it returns
+    /// there after every send instead of entering RX, saving the RX current.
+    ///
+    /// Hardware auto-ack still works, as the chip listens for the ACK by itself, but ACK
+    /// payloads can't be read and nothing is received. Takes effect on the next `config` or
+    /// send.
+    pub fn set_tx_only(&mut self, tx_only: bool) {
+        self.tx_only = tx_only;
     }
 
-    fn write_register(&mut self, register: u8, value: &[u8]) -> Result<(), Error<E>> {
-        self.csn.set_low().map_err(|_| Error::Gpio)?;
-
-        self.spi
-            .write(&[Instruction::W_REGISTER | (Instruction::REGISTER_MASK & register)])?;
-        self.spi.write(value)?;
-        self.csn.set_high().map_err(|_| Error::Gpio)?;
-        Ok(())
+    /// Set the FEATURE register bits: dynamic payload length, ACK payloads and the no-ack
+    /// transmit command. The value is remembered and reapplied by `config`.
+    pub fn set_feature(&mut self, dpl: bool, ack_pay: bool, dyn_ack: bool) -> Result<(), Error<E>> {
+        self.feature = ((dpl as u8) << BitMnemonic::EN_DPL)
+            | ((ack_pay as u8) << BitMnemonic::EN_ACK_PAY)
+            | ((dyn_ack as u8) << BitMnemonic::EN_DYN_ACK);
+        self.write_feature()
     }
 
-    pub fn power_down(&mut self) -> Result<(), Error<E>> {
-        self.ce.set_low().map_err(|_| Error::Gpio)?;
-        self.config_register(Memory::CONFIG, &MIRF_CONFIG)?;
-        Ok(())
+    /// Send the ACTIVATE command, which clones and non-plus parts need before FEATURE, DYN_PD
+    /// and the related commands become usable.
+    ///
+    /// On those parts ACTIVATE toggles the features, so calling it twice turns them off again.
+    /// Genuine nRF24L01+ chips ignore it. `set_feature` and `config` only send it when a FEATURE
+    /// write didn't stick.
+    pub fn activate_features(&mut self) -> Result<(), Error<E>> {
+        self.transaction(&mut [Instruction::ACTIVATE, 0x73])
     }
 
-    fn power_up_rx(&mut self) -> Result<(), Error<E>> {
-        self.tx_power_status = false;
-        self.ce.set_low().map_err(|_| Error::Gpio)?;
-        self.config_register(
-            Memory::CONFIG,
-            &(MIRF_CONFIG | ((1 << BitMnemonic::PWR_UP) | (1 << BitMnemonic::PRIM_RX))),
-        )?;
-        self.ce.set_high().map_err(|_| Error::Gpio)?;
-        self.config_register(
-            Memory::STATUS,
-            &((1 << BitMnemonic::TX_DS) | (1 << BitMnemonic::MAX_RT)),
-        )?;
-        Ok(())
+    /// Check FEATURE accepts writes, sending ACTIVATE if it doesn't, and return whether
+    /// ACTIVATE was needed, e.g. to log the module type.
+    ///
+    /// A test pattern with all three feature bits is written and read back, then the
+    /// remembered FEATURE value is restored. `Error::VerificationFailed` for FEATURE means the
+    /// writes didn't stick even after ACTIVATE.
+    pub fn ensure_features_writable(&mut self) -> Result<bool, Error<E>> {
+        let pattern = (1 << BitMnemonic::EN_DPL)
+            | (1 << BitMnemonic::EN_ACK_PAY)
+            | (1 << BitMnemonic::EN_DYN_ACK);
+        self.config_register(Memory::FEATURE, &pattern)?;
+        let needed_activate = self.read_register(Memory::FEATURE)? != pattern;
+        if needed_activate {
+            self.activate_features()?;
+            self.config_register(Memory::FEATURE, &pattern)?;
+            if self.read_register(Memory::FEATURE)? != pattern {
+                return Err(Error::VerificationFailed {
+                    register: Memory::FEATURE,
+                });
+            }
+        }
+        let feature = self.feature;
+        self.config_register(Memory::FEATURE, &feature)?;
+        Ok(needed_activate)
     }
 
-    fn power_up_tx(&mut self) -> Result<(), Error<E>> {
-        self.tx_power_status = true;
-        self.config_register(
-            Memory::CONFIG,
-            &(MIRF_CONFIG | ((1 << BitMnemonic::PWR_UP) | (0 << BitMnemonic::PRIM_RX))),
-        )?;
+    fn write_feature(&mut self) -> Result<(), Error<E>> {
+        let feature = self.feature;
+        self.config_register(Memory::FEATURE, &feature)?;
+        if feature != 0 && self.read_register(Memory::FEATURE)? != feature {
+            self.activate_features()?;
+            self.config_register(Memory::FEATURE, &feature)?;
+        }
         Ok(())
     }
 
-    fn flush_rx(&mut self) -> Result<(), Error<E>> {
-        self.csn.set_low().map_err(|_| Error::Gpio)?;
-        self.spi.write(&[Instruction::FLUSH_RX])?;
-        self.csn.set_high().map_err(|_| Error::Gpio)?;
-        Ok(())
+    /// Read the FEATURE register back from the chip.
+    ///
+    /// Some clones and non-plus parts ignore FEATURE writes until the ACTIVATE command
+    /// (0x50, 0x73) has been sent, in which case this reads 0.
+    pub fn feature(&mut self) -> Result<u8, Error<E>> {
+        self.read_register(Memory::FEATURE)
     }
 
-    pub fn free(self) -> (SPI, CSN, CE) {
-        (self.spi, self.csn, self.ce)
+    /// Read the DYN_PD register back from the chip, a bitmask of the pipes with dynamic
+    /// payload length enabled.
+    ///
+    /// Together with `feature` this confirms dynamic payloads actually took effect, so a
+    /// non-compliant module can be reported at startup.
+    pub fn dynamic_payload_pipes(&mut self) -> Result<u8, Error<E>> {
+        self.read_register(Memory::DYN_PD)
     }
 
-    pub fn set_raddr(&mut self, addr: &[u8]) -> Result<(), Error<E>> {
-        self.ce.set_low().map_err(|_| Error::Gpio)?;
-        self.write_register(Memory::RX_ADDR_P1, addr)?;
-        self.ce.set_high().map_err(|_| Error::Gpio)?;
-        Ok(())
-    }
+    /// Set up everything ACK payloads depend on for `pipes` (pipe numbers 0-5): EN_DPL and
+    /// EN_ACK_PAY in FEATURE, dynamic payload length in DYN_PD and auto-ack in EN_AA.
+    ///
+    /// ACK payloads ride on auto-ack packets and always have a dynamic length, so all three
+    /// are required. The transmitting side needs pipe 0 listed, since its ACKs arrive there.
+    /// The payload size becomes dynamic; a later `config` resets DYN_PD to pipes 0 and 1.
+    pub fn enable_ack_payloads(&mut self, pipes: &[u8]) -> Result<(), Error<E>> {
+        let mut mask = 0;
+        for &pipe in pipes {
+            if pipe > 5 {
+                return Err(Error::InvalidArgument);
+            }
+            mask |= 1 << pipe;
+        }
 
-    pub fn set_taddr(&mut self, addr: &[u8]) -> Result<(), Error<E>> {
-        self.write_register(Memory::RX_ADDR_P0, addr)?;
-        self.write_register(Memory::TX_ADDR, addr)?;
+        self.feature |= (1 << BitMnemonic::EN_DPL) | (1 << BitMnemonic::EN_ACK_PAY);
+        self.write_feature()?;
+        let dyn_pd = self.read_register(Memory::DYN_PD)? | mask;
+        self.config_register(Memory::DYN_PD, &dyn_pd)?;
+        let en_aa = self.read_register(Memory::EN_AA)? | mask;
+        self.config_register(Memory::EN_AA, &en_aa)?;
+        self.payload_size = 0;
         Ok(())
     }
 
-    pub fn get_status(&mut self) -> Result<u8, Error<E>> {
-        let response = self.read_register(Memory::STATUS)?;
-        Ok(response)
+    /// Switch to `channel`
+    pub fn set_channel(&mut self, channel: Channel) -> Result<(), Error<E>> {
+        self.set_channel_raw(channel.number())
     }
 
-    pub fn send(&mut self, data: &[u8]) -> Result<(), Error<E>> {
-        let _ = self.get_status()?; // I'm not entirely sure why, but Mirf does this, so we do as well.
-        while self.tx_power_status {
-            let status = self.get_status()?;
-            if (status & ((1 << BitMnemonic::TX_DS) | (1 << BitMnemonic::MAX_RT))) != 0 {
-                self.tx_power_status = false;
-                break;
-            }
+    /// Like `set_channel`, but fails with `Error::InvalidArgument` if `channel` is closer to
+    /// any of `occupied` than `min_channel_spacing` allows at the configured data rate.
+    pub fn set_channel_checked(
+        &mut self,
+        channel: Channel,
+        occupied: &[Channel],
+    ) -> Result<(), Error<E>> {
+        let spacing = min_channel_spacing(self.data_rate()?);
+        if occupied
+            .iter()
+            .any(|other| channel.number().abs_diff(other.number()) < spacing)
+        {
+            return Err(Error::InvalidArgument);
         }
+        self.set_channel(channel)
+    }
 
-        self.ce.set_low().map_err(|_| Error::Gpio)?;
-        self.power_up_tx()?;
+    /// Write `channel` to RF_CH unchecked, only its lower 7 bits are used
+    pub fn set_channel_raw(&mut self, channel: u8) -> Result<(), Error<E>> {
+        self.channel = channel & 0x7F;
+        let channel = self.channel;
+        self.config_register(Memory::RF_CH, &channel)
+    }
 
-        self.csn.set_low().map_err(|_| Error::Gpio)?;
-        self.spi.write(&[Instruction::FLUSH_TX])?;
-        self.csn.set_high().map_err(|_| Error::Gpio)?;
+    /// Read the lost packet counter (PLOS_CNT) and reset it.
+    ///
+    /// The chip only resets the counter when RF_CH is written, so the cached channel is written
+    /// back. The channel doesn't change.
+    pub fn take_packets_lost(&mut self) -> Result<u8, Error<E>> {
+        let observe_tx = self.read_register(Memory::OBSERVE_TX)?;
+        let channel = self.channel;
+        self.config_register(Memory::RF_CH, &channel)?;
+        Ok(observe_tx >> BitMnemonic::PLOS_CNT)
+    }
 
-        self.csn.set_low().map_err(|_| Error::Gpio)?;
-        self.spi.write(&[Instruction::W_TX_PAYLOAD])?;
-        self.spi.write(data)?;
-        self.csn.set_high().map_err(|_| Error::Gpio)?;
+    /// Classify the link from the lost packet counter, the retransmits of the last packet and
+    /// 8 RPD samples taken 50µs apart.
+    ///
+    /// `Bad` means 8 or more lost packets or at least 10 retransmits, `Marginal` any lost packet,
+    /// 3 or more retransmits, or retransmits with the received power mostly below -64dBm.
+    /// The chip silently drops packets failing CRC, so those can't be counted directly.
+    pub fn link_health(&mut self) -> Result<LinkHealth, Error<E>> {
+        const SAMPLES: u8 = 8;
+        let strong = self.estimate_signal(SAMPLES)?;
+        let observe_tx = self.read_register(Memory::OBSERVE_TX)?;
+        let lost = observe_tx >> BitMnemonic::PLOS_CNT;
+        let retransmits = observe_tx & 0x0F;
 
-        self.ce.set_high().map_err(|_| Error::Gpio)?;
-        Ok(())
+        Ok(if lost >= 8 || retransmits >= 10 {
+            LinkHealth::Bad
+        } else if lost > 0 || retransmits >= 3 || (retransmits > 0 && strong < SAMPLES / 2) {
+            LinkHealth::Marginal
+        } else {
+            LinkHealth::Good
+        })
     }
 
-    pub fn is_sending(&mut self) -> Result<bool, Error<E>> {
+    /// Sample RPD `samples` times, 50µs apart, and return how many saw a signal.
+    ///
+    /// RPD is a single threshold at -64dBm, so this is a coarse ordinal proxy for signal
+    /// strength, not an RSSI: a weak link reads 0 whatever its actual level, and a count in
+    /// between only means the carrier was above the threshold part of the time. It is only
+    /// meaningful in RX mode.
+    pub fn estimate_signal(&mut self, samples: u8) -> Result<u8, Error<E>> {
+        let mut strong = 0;
+        for _ in 0..samples {
+            strong += self.read_register(Memory::RPD)? & 1;
+            self.delay.delay_us(RPD_SAMPLE_INTERVAL_US);
+        }
+        Ok(strong)
+    }
+
+    /// Listen on `channel` briefly and report whether RPD saw a signal above -64dBm, e.g. to
+    /// check a channel is free before hopping to it.
+    ///
+    /// The radio leaves its channel for about 200µs: 130µs to settle in RX, then 50µs for RPD
+    /// to latch. The channel and CONFIG are restored afterwards, also when the probe fails,
+    /// and CE goes back high if it was. From power down, the 1.5ms start-up is waited first.
+    /// Fails with `Error::InvalidArgument` while a transmission is in progress.
+    pub fn channel_activity(&mut self, channel: Channel) -> Result<bool, Error<E>> {
         if self.tx_power_status {
-            let status = self.get_status()?;
-            if (status & ((1 << BitMnemonic::TX_DS) | (1 << BitMnemonic::MAX_RT))) != 0 {
-                self.power_up_rx()?;
-                return Ok(false);
-            }
+            return Err(Error::InvalidArgument);
+        }
+        let previous_channel = self.channel;
+        let previous_config = self.read_register(Memory::CONFIG)?;
+        let ce_was_high = self.ce_state;
 
-            return Ok(true);
+        let probe = |nrf24l01: &mut Self| -> Result<bool, Error<E>> {
+            nrf24l01.set_ce_low()?;
+            nrf24l01.set_channel_raw(channel.number())?;
+            nrf24l01.enter_rx()?;
+            nrf24l01.delay.delay_us(RPD_SAMPLE_INTERVAL_US);
+            Ok(nrf24l01.read_register(Memory::RPD)? & 1 != 0)
+        };
+        let active = probe(self);
+
+        self.set_ce_low()?;
+        self.set_channel_raw(previous_channel)?;
+        self.config_register(Memory::CONFIG, &previous_config)?;
+        self.powered_up = previous_config & (1 << BitMnemonic::PWR_UP) != 0;
+        if ce_was_high {
+            self.set_ce_high()?;
+            self.delay.delay_us(TSTBY2A_US);
         }
-        Ok(false)
+        active
     }
 
-    pub fn data_ready(&mut self) -> Result<bool, Error<E>> {
-        let status = self.get_status()?;
-        if (status & (1 << BitMnemonic::RX_DR)) != 0 {
-            return Ok(true);
-        }
-        let fifo_empty = self.rx_fifo_empty()?;
-        Ok(!fifo_empty)
+    /// The configured channel number
+    pub fn channel(&self) -> u8 {
+        self.channel
     }
 
-    fn rx_fifo_empty(&mut self) -> Result<bool, Error<E>> {
-        let fifo_status = self.read_register(Memory::FIFO_STATUS)?;
-        if fifo_status & (1 << BitMnemonic::RX_EMPTY) != 0 {
-            return Ok(true);
-        }
-        Ok(false)
+    /// Read RF_CH back from the chip, e.g. to check a `set_channel` took effect
+    pub fn current_channel(&mut self) -> Result<u8, Error<E>> {
+        Ok(self.read_register(Memory::RF_CH)? & 0x7F)
     }
 
+    /// Set the auto retransmit delay to `(delay_steps + 1) * 250µs` and the number of
+    /// retransmits to `count` (0 disables retransmission). Both must be at most 15.
+    pub fn set_retransmission(&mut self, delay_steps: u8, count: u8) -> Result<(), Error<E>> {
+        if delay_steps > 15 || count > 15 {
+            return Err(Error::InvalidArgument);
+        }
+        self.config_register(
+            Memory::SETUP_RETR,
+            &((delay_steps << BitMnemonic::ARD) | (count << BitMnemonic::ARC)),
+        )
+    }
+
+    /// Set the auto retransmit delay to at least `us`, keeping the retransmit count.
+    ///
+    /// The delay is rounded up to the next 250µs step and limited to the 250 to 4000µs range
+    /// the chip supports, e.g. 600 becomes 750.
+    pub fn set_retransmit_delay_us(&mut self, us: u16) -> Result<(), Error<E>> {
+        let count = self.read_register(Memory::SETUP_RETR)? & 0x0F;
+        self.set_retransmission(ard_steps_for_us(us), count)
+    }
+
+    /// The configured auto retransmit delay in microseconds
+    pub fn retransmit_delay_us(&mut self) -> Result<u16, Error<E>> {
+        let setup_retr = self.read_register(Memory::SETUP_RETR)?;
+        Ok(ard_steps_to_us(setup_retr >> BitMnemonic::ARD))
+    }
+
+    /// Set the air data rate in RF_SETUP.
+    ///
+    /// Slower rates make ACKs take longer, so with auto-ack on for any pipe the retransmit
+    /// delay is raised to the minimum the new rate needs if it is shorter (see
+    /// `auto_ard_for_ack`), assuming full 32 byte ACK payloads if EN_ACK_PAY is set. A longer
+    /// delay is left alone. `set_auto_ard(false)` turns the adjustment off.
+    pub fn set_data_rate(&mut self, data_rate: DataRate) -> Result<(), Error<E>> {
+        let bits = match data_rate {
+            DataRate::R250Kbps => 1 << BitMnemonic::RF_DR_LOW,
+            DataRate::R1Mbps => 0,
+            DataRate::R2Mbps => 1 << BitMnemonic::RF_DR,
+        };
+        let mask = (1 << BitMnemonic::RF_DR_LOW) | (1 << BitMnemonic::RF_DR);
+        let rf_setup = (self.read_register(Memory::RF_SETUP)? & !mask) | bits;
+        self.config_register(Memory::RF_SETUP, &rf_setup)?;
+
+        if !self.auto_ard || self.read_register(Memory::EN_AA)? & 0x3F == 0 {
+            return Ok(());
+        }
+        let ack_payload_len = if self.feature & (1 << BitMnemonic::EN_ACK_PAY) != 0 {
+            MAX_PAYLOAD as u8
+        } else {
+            0
+        };
+        let min_steps = min_ard_steps(ack_payload_len, data_rate);
+        let setup_retr = self.read_register(Memory::SETUP_RETR)?;
+        if setup_retr >> BitMnemonic::ARD < min_steps {
+            self.set_retransmission(min_steps, setup_retr & 0x0F)?;
+        }
+        Ok(())
+    }
+
+    /// Whether `set_data_rate` raises the retransmit delay to what the new rate needs, on by
+    /// default
+    pub fn set_auto_ard(&mut self, enabled: bool) {
+        self.auto_ard = enabled;
+    }
+
+    /// Read the configured air data rate from RF_SETUP
+    pub fn data_rate(&mut self) -> Result<DataRate, Error<E>> {
+        let rf_setup = self.read_register(Memory::RF_SETUP)?;
+        Ok(if rf_setup & (1 << BitMnemonic::RF_DR_LOW) != 0 {
+            DataRate::R250Kbps
+        } else if rf_setup & (1 << BitMnemonic::RF_DR) != 0 {
+            DataRate::R2Mbps
+        } else {
+            DataRate::R1Mbps
+        })
+    }
+
+    /// Read the configured address width from SETUP_AW.
+    ///
+    /// Returns `Error::InvalidState` for the illegal value 0b00.
+    pub fn address_width(&mut self) -> Result<AddressWidth, Error<E>> {
+        match self.read_register(Memory::SETUP_AW)? & 0b11 {
+            0b01 => Ok(AddressWidth::Three),
+            0b10 => Ok(AddressWidth::Four),
+            0b11 => Ok(AddressWidth::Five),
+            _ => Err(Error::InvalidState),
+        }
+    }
+
+    /// Combine the settings both ends of a link must agree on into one value, to be compared
+    /// with the peer's, e.g. in a handshake payload.
+    ///
+    /// Covers the channel, data rate, address width, CRC mode and whether auto-ack is on for
+    /// any pipe. Addresses are left out, as they legitimately differ between roles. Equal
+    /// values mean equal settings.
+    pub fn compatibility_fingerprint(&mut self) -> Result<u32, Error<E>> {
+        let mut values = [0; 4];
+        self.read_registers(
+            &[
+                Memory::RF_CH,
+                Memory::SETUP_AW,
+                Memory::CONFIG,
+                Memory::EN_AA,
+            ],
+            &mut values,
+        )?;
+        let [channel, address_width, config, auto_ack] = values;
+        let channel = channel & 0x7F;
+        let address_width = address_width & 0b11;
+        let data_rate = match self.data_rate()? {
+            DataRate::R250Kbps => 0,
+            DataRate::R1Mbps => 1,
+            DataRate::R2Mbps => 2,
+        };
+        // CRCO means nothing with CRC off
+        let crc = if config & (1 << BitMnemonic::EN_CRC) != 0 {
+            (config >> BitMnemonic::CRCO) & 0b11
+        } else {
+            0
+        };
+        let auto_ack = (auto_ack & 0x3F != 0) as u32;
+
+        Ok(channel as u32
+            | data_rate << 7
+            | (address_width as u32) << 9
+            | (crc as u32) << 11
+            | auto_ack << 13)
+    }
+
+    /// Set the shortest retransmit delay that still lets an ACK with `ack_payload_len` bytes of
+    /// payload arrive at the current data rate, keeping the retransmit count.
+    ///
+    /// The delay is the 130µs RX/TX turnaround plus the airtime of the ACK packet (see
+    /// `airtime_us`), rounded up to a 250µs step, assuming 5 byte addresses and a 2 byte CRC.
+    /// E.g. a 32 byte ACK payload needs 500µs at 1Mbps and 1500µs at 250kbps.
+    pub fn auto_ard_for_ack(&mut self, ack_payload_len: u8) -> Result<(), Error<E>> {
+        if ack_payload_len as usize > MAX_PAYLOAD {
+            return Err(Error::InvalidArgument);
+        }
+        let data_rate = self.data_rate()?;
+        let count = self.read_register(Memory::SETUP_RETR)? & 0x0F;
+        self.set_retransmission(min_ard_steps(ack_payload_len, data_rate), count)
+    }
+
+    /// Probe whether the chip is an nRF24L01+ by selecting 250kbps, which only plus parts
+    /// support, and checking that RF_DR_LOW reads back set. RF_SETUP is restored afterwards.
+    ///
+    /// Clones don't always behave like the part they claim to be, so treat the result as a
+    /// hint. It is also remembered and available from `chip_variant`.
+    pub fn detect_variant(&mut self) -> Result<ChipVariant, Error<E>> {
+        let rf_setup = self.read_register(Memory::RF_SETUP)?;
+        // RF_DR_LOW together with RF_DR is the reserved rate setting, so clear RF_DR
+        let probe = (rf_setup & !(1 << BitMnemonic::RF_DR)) | (1 << BitMnemonic::RF_DR_LOW);
+        self.config_register(Memory::RF_SETUP, &probe)?;
+        let read_back = self.read_register(Memory::RF_SETUP)?;
+        self.config_register(Memory::RF_SETUP, &rf_setup)?;
+
+        let variant = if read_back & (1 << BitMnemonic::RF_DR_LOW) != 0 {
+            ChipVariant::Plus
+        } else {
+            ChipVariant::NonPlus
+        };
+        self.chip_variant = Some(variant);
+        Ok(variant)
+    }
+
+    /// The variant found by the last `detect_variant`
+    pub fn chip_variant(&self) -> Option<ChipVariant> {
+        self.chip_variant
+    }
+
+    /// Disable CRC, auto-ack and retransmission for maximum throughput.
+    ///
+    /// Corrupted packets are no longer dropped by the chip and nothing gets retransmitted, so
+    /// integrity checking is entirely up to the application. Both ends must use this mode.
+    pub fn raw_mode(&mut self) -> Result<(), Error<E>> {
+        // EN_AA forces EN_CRC on, so it has to go first
+        self.config_register(Memory::EN_AA, &0)?;
+        self.config_register(Memory::SETUP_RETR, &0)?;
+        self.config_base &= !(1 << BitMnemonic::EN_CRC);
+        let config = self.read_register(Memory::CONFIG)? & !(1 << BitMnemonic::EN_CRC);
+        self.config_register(Memory::CONFIG, &config)
+    }
+
+    /// Enable or disable auto-ack for `pipe` in EN_AA.
+    ///
+    /// EN_CRC is left alone: CRC is a separate setting, see `set_crc`. While auto-ack is on for
+    /// any pipe the chip forces CRC on regardless of EN_CRC, so turning auto-ack off on every
+    /// pipe brings back whatever `set_crc` configured.
+    pub fn set_auto_ack(&mut self, pipe: Pipe, enabled: bool) -> Result<(), Error<E>> {
+        let mut auto_ack = self.read_register(Memory::EN_AA)?;
+        if enabled {
+            auto_ack |= 1 << pipe.number();
+        } else {
+            auto_ack &= !(1 << pipe.number());
+        }
+        self.config_register(Memory::EN_AA, &auto_ack)
+    }
+
+    /// Set the CRC length in CONFIG, independently of auto-ack (see `set_auto_ack`).
+    ///
+    /// `CrcMode::Disabled` only takes effect with auto-ack off on every pipe. The setting is
+    /// kept across `power_down` and mode changes.
+    pub fn set_crc(&mut self, crc: CrcMode) -> Result<(), Error<E>> {
+        let bits = match crc {
+            CrcMode::Disabled => 0,
+            CrcMode::OneByte => 1 << BitMnemonic::EN_CRC,
+            CrcMode::TwoBytes => (1 << BitMnemonic::EN_CRC) | (1 << BitMnemonic::CRCO),
+        };
+        let mask = (1 << BitMnemonic::EN_CRC) | (1 << BitMnemonic::CRCO);
+        self.config_base = (self.config_base & !mask) | bits;
+        let config = (self.read_register(Memory::CONFIG)? & !mask) | bits;
+        self.config_register(Memory::CONFIG, &config)
+    }
+
+    /// Choose which events drive the IRQ pin: a `true` masks RX_DR, TX_DS or MAX_RT
+    /// respectively. The flags still get set in STATUS either way.
+    ///
+    /// The masks are kept across `power_down` and mode changes.
+    pub fn set_interrupt_mask(
+        &mut self,
+        rx_dr: bool,
+        tx_ds: bool,
+        max_rt: bool,
+    ) -> Result<(), Error<E>> {
+        let mask = (1 << BitMnemonic::MASK_RX_DR)
+            | (1 << BitMnemonic::MASK_TX_DS)
+            | (1 << BitMnemonic::MASK_MAX_RT);
+        let bits = ((rx_dr as u8) << BitMnemonic::MASK_RX_DR)
+            | ((tx_ds as u8) << BitMnemonic::MASK_TX_DS)
+            | ((max_rt as u8) << BitMnemonic::MASK_MAX_RT);
+        self.config_base = (self.config_base & !mask) | bits;
+        let config = (self.read_register(Memory::CONFIG)? & !mask) | bits;
+        self.config_register(Memory::CONFIG, &config)
+    }
+
+    /// Set up a receive-only node: mask TX_DS and MAX_RT, so the IRQ pin only asserts when
+    /// new data arrives, and start listening.
+    pub fn configure_rx_interrupt(&mut self) -> Result<(), Error<E>> {
+        self.set_interrupt_mask(false, true, true)?;
+        self.start_listening()
+    }
+
+    /// True if auto-ack is enabled for `pipe` (0 to 5) in EN_AA
+    pub fn auto_ack_enabled(&mut self, pipe: u8) -> Result<bool, Error<E>> {
+        let pipe = Pipe::new(pipe).ok_or(Error::InvalidArgument)?;
+        let auto_ack = self.read_register(Memory::EN_AA)?;
+        Ok(auto_ack & (1 << pipe.number()) != 0)
+    }
+
+    /// Check that every enabled pipe with dynamic payloads also has auto-ack enabled, and that
+    /// EN_DPL is set in FEATURE. Returns `Error::MisconfiguredPipes` listing the pipes that
+    /// won't work.
+    pub fn validate_config(&mut self) -> Result<(), Error<E>> {
+        let mut values = [0; 4];
+        self.read_registers(
+            &[
+                Memory::EN_AA,
+                Memory::DYN_PD,
+                Memory::FEATURE,
+                Memory::EN_RXADDR,
+            ],
+            &mut values,
+        )?;
+        let [auto_ack, dynamic, feature, enabled] = values;
+
+        let mut misconfigured = dynamic & !auto_ack;
+        if feature & (1 << BitMnemonic::EN_DPL) == 0 {
+            misconfigured |= dynamic;
+        }
+        misconfigured &= enabled & 0x3F;
+        if misconfigured != 0 {
+            return Err(Error::MisconfiguredPipes(misconfigured));
+        }
+        Ok(())
+    }
+
+    fn config_register(&mut self, register: u8, value: &u8) -> Result<(), Error<E>> {
+        self.write_register(register, &[*value])
+    }
+
+    fn read_register(&mut self, register: u8) -> Result<u8, Error<E>> {
+        let mut buffer = [0];
+        self.read_register_into(register, &mut buffer)?;
+        Ok(buffer[0])
+    }
+
+    /// Read the single byte registers `registers` into `out`, in the same order
+    fn read_registers(&mut self, registers: &[u8], out: &mut [u8]) -> Result<(), Error<E>> {
+        if out.len() < registers.len() {
+            return Err(Error::InvalidArgument);
+        }
+        for (&register, value) in registers.iter().zip(out.iter_mut()) {
+            *value = self.read_register(register)?;
+        }
+        Ok(())
+    }
+
+    // Relies on `transfer` filling the whole buffer, as the embedded-hal contract requires.
+    // The STATUS byte clocked out with the command is checked to catch a floating MISO line.
+    fn read_register_into(&mut self, register: u8, buf: &mut [u8]) -> Result<(), Error<E>> {
+        self.csn.set_low().map_err(|_| Error::Gpio)?;
+        let mut command = [Instruction::R_REGISTER | (Instruction::REGISTER_MASK & register)];
+        self.spi.transfer(&mut command)?;
+        self.spi.transfer(buf)?;
+        self.csn.set_high().map_err(|_| Error::Gpio)?;
+        self.last_status = Status(command[0]);
+        Self::check_status(command[0])
+    }
+
+    /// STATUS bit 7 is reserved and always reads 0, so a set bit means nothing drives MISO
+    fn check_status(status: u8) -> Result<(), Error<E>> {
+        if status & 0x80 != 0 {
+            return Err(Error::NotConnected);
+        }
+        Ok(())
+    }
+
+    fn write_register(&mut self, register: u8, value: &[u8]) -> Result<(), Error<E>> {
+        // Clearing STATUS flags is fine in any mode, configuration needs CE low
+        if register == Memory::STATUS {
+            return self.spi_write_register(register, value);
+        }
+        self.with_ce_low(|nrf24l01| nrf24l01.spi_write_register(register, value))
+    }
+
+    fn spi_write_register(&mut self, register: u8, value: &[u8]) -> Result<(), Error<E>> {
+        self.csn.set_low().map_err(|_| Error::Gpio)?;
+        let mut command = [Instruction::W_REGISTER | (Instruction::REGISTER_MASK & register)];
+        self.spi.transfer(&mut command)?;
+        self.spi.write(value)?;
+        self.csn.set_high().map_err(|_| Error::Gpio)?;
+        self.last_status = Status(command[0]);
+        Ok(())
+    }
+
+    /// Clock out `frame` (a command byte and its data) in one CSN low window, leaving what
+    /// the chip sent back in `frame` and the STATUS byte in `last_status`
+    fn transaction(&mut self, frame: &mut [u8]) -> Result<(), Error<E>> {
+        self.csn.set_low().map_err(|_| Error::Gpio)?;
+        self.spi.transfer(frame)?;
+        self.csn.set_high().map_err(|_| Error::Gpio)?;
+        self.last_status = Status(frame[0]);
+        Ok(())
+    }
+
+    /// STATUS as clocked out during the most recent SPI transaction, without another bus
+    /// round-trip, `Status(0)` before the first one.
+    ///
+    /// Every chip command returns STATUS alongside its command byte, and the driver keeps the
+    /// last one. Flags that got set since then aren't reflected, use `status_fast` when it
+    /// has to be current.
+    pub fn last_status(&self) -> Status {
+        self.last_status
+    }
+
+    /// Tell the driver how CE is wired.
+    ///
+    /// With `CeMode::TiedHigh` the radio never enters standby-I: it transmits as soon as a
+    /// payload is loaded and otherwise sits in RX (about 12mA) or TX standby-II. Registers are
+    /// also written with CE high, so mode changes take effect immediately.
+    pub fn set_ce_mode(&mut self, mode: CeMode) {
+        self.ce_mode = mode;
+        if mode == CeMode::TiedHigh {
+            self.ce_state = true;
+        }
+    }
+
+    /// Retransmit statistics of the transmissions completed since the last `reset_stats`.
+    ///
+    /// Every transmission seen to complete by `is_sending`, `poll_send`, `on_irq` or a blocking
+    /// send is counted, at the cost of one OBSERVE_TX read each. `stream` only counts its last
+    /// packet.
+    pub fn tx_stats(&self) -> TxStats {
+        self.tx_stats
+    }
+
+    /// Start the statistics of `tx_stats` over
+    pub fn reset_stats(&mut self) {
+        self.tx_stats = TxStats::default();
+    }
+
+    /// True if the driver last drove CE high, or CE is tied high.
+    ///
+    /// This is the commanded state, an `OutputPin` can't be read back, so a wiring fault
+    /// won't show here.
+    pub fn ce_is_high(&self) -> bool {
+        self.ce_state
+    }
+
+    fn set_ce_low(&mut self) -> Result<(), Error<E>> {
+        if self.ce_mode == CeMode::TiedHigh {
+            return Ok(());
+        }
+        self.ce.set_low().map_err(|_| Error::Gpio)?;
+        self.ce_state = false;
+        Ok(())
+    }
+
+    fn set_ce_high(&mut self) -> Result<(), Error<E>> {
+        if self.ce_mode == CeMode::TiedHigh {
+            return Ok(());
+        }
+        self.ce.set_high().map_err(|_| Error::Gpio)?;
+        self.ce_state = true;
+        Ok(())
+    }
+
+    /// Run `f` with CE low, restoring CE afterwards if it was high
+    fn with_ce_low<R>(
+        &mut self,
+        f: impl FnOnce(&mut Self) -> Result<R, Error<E>>,
+    ) -> Result<R, Error<E>> {
+        let ce_was_high = self.ce_state;
+        if ce_was_high {
+            self.set_ce_low()?;
+        }
+        let result = f(self);
+        if ce_was_high {
+            self.set_ce_high()?;
+        }
+        result
+    }
+
+    /// Power the radio down, clearing only PWR_UP so CRC, interrupt masks and PRIM_RX are
+    /// still in place when it powers up again.
+    pub fn power_down(&mut self) -> Result<(), Error<E>> {
+        self.set_ce_low()?;
+        let config = self.read_register(Memory::CONFIG)? & !(1 << BitMnemonic::PWR_UP);
+        self.config_register(Memory::CONFIG, &config)?;
+        self.powered_up = false;
+        Ok(())
+    }
+
+    /// Put the radio into power down, its lowest power state (about 900nA), with CE low.
+    ///
+    /// All registers keep their values. See `wake` for powering up again.
+    pub fn sleep(&mut self) -> Result<(), Error<E>> {
+        self.power_down()
+    }
+
+    /// Power up after `sleep` into standby, waiting the 1.5ms Tpd2stby, and check the radio
+    /// is actually awake.
+    ///
+    /// Returns `Error::VerificationFailed` for CONFIG if the chip lost its configuration while
+    /// asleep (e.g. a brownout reset it, which calls for `config` again) or PWR_UP doesn't
+    /// read back set. The mode (RX or TX) is kept, but CE stays low until the next `send` or
+    /// `start_listening`.
+    pub fn wake(&mut self) -> Result<(), Error<E>> {
+        let mode_bits = (1 << BitMnemonic::PWR_UP) | (1 << BitMnemonic::PRIM_RX);
+        let config = self.read_register(Memory::CONFIG)?;
+        if config & !mode_bits != self.config_base {
+            return Err(Error::VerificationFailed {
+                register: Memory::CONFIG,
+            });
+        }
+
+        let config = config | (1 << BitMnemonic::PWR_UP);
+        self.config_register(Memory::CONFIG, &config)?;
+        self.wait_power_up();
+        if self.read_register(Memory::CONFIG)? != config {
+            return Err(Error::VerificationFailed {
+                register: Memory::CONFIG,
+            });
+        }
+        Ok(())
+    }
+
+    /// Enter RX: CE low, write the pipe 0 address from `set_pipe0_rx_address` back if a send
+    /// swapped the TX address in, set PWR_UP and PRIM_RX (waiting 1.5ms if the radio was
+    /// powered down), raise CE and wait the 130µs Tstby2a settle time. TX_DS and MAX_RT are
+    /// cleared.
+    ///
+    /// A transmission still in progress is cut off, check `is_sending` first.
+    pub fn start_listening(&mut self) -> Result<(), Error<E>> {
+        self.power_up_rx()
+    }
+
+    /// Switch PRIM_RX to `role`, leaving PWR_UP and the rest of CONFIG as they are.
+    ///
+    /// CE isn't touched: with CE low the radio stays in standby until it is raised, with CE
+    /// high the new role takes effect right away. Meant for state machines managing CE and
+    /// power themselves; `start_listening` and `send` set the role on their own.
+    pub fn set_role(&mut self, role: Role) -> Result<(), Error<E>> {
+        let config = self.read_register(Memory::CONFIG)?;
+        let config = match role {
+            Role::PrimaryRx => config | (1 << BitMnemonic::PRIM_RX),
+            Role::PrimaryTx => config & !(1 << BitMnemonic::PRIM_RX),
+        };
+        self.spi_write_register(Memory::CONFIG, &[config])
+    }
+
+    /// Stop receiving by lowering CE, leaving the radio powered up in standby-I. The RX FIFO
+    /// keeps its packets.
+    pub fn stop_listening(&mut self) -> Result<(), Error<E>> {
+        self.set_ce_low()
+    }
+
+    fn power_up_rx(&mut self) -> Result<(), Error<E>> {
+        self.enter_rx()?;
+        self.clear_tx_flags()
+    }
+
+    fn clear_tx_flags(&mut self) -> Result<(), Error<E>> {
+        self.config_register(
+            Memory::STATUS,
+            &((1 << BitMnemonic::TX_DS) | (1 << BitMnemonic::MAX_RT)),
+        )
+    }
+
+    /// Power up in TX standby: CE low and PRIM_RX clear
+    fn standby_tx(&mut self) -> Result<(), Error<E>> {
+        self.set_ce_low()?;
+        self.tx_power_status = false;
+        let config = self.config_base | (1 << BitMnemonic::PWR_UP);
+        self.config_register(Memory::CONFIG, &config)?;
+        self.wait_power_up();
+        Ok(())
+    }
+
+    /// Leave TX once a transmission is done, to RX or to TX standby if `tx_only` is set
+    fn finish_tx(&mut self) -> Result<(), Error<E>> {
+        let retransmits = self.read_register(Memory::OBSERVE_TX)? & 0x0F;
+        self.tx_stats.record(retransmits);
+        if self.tx_only {
+            self.standby_tx()
+        } else {
+            self.enter_rx()
+        }
+    }
+
+    fn enter_rx(&mut self) -> Result<(), Error<E>> {
+        self.tx_power_status = false;
+        self.set_ce_low()?;
+        if let Some(addr) = self.pipe0_address {
+            if self.pipe0_holds_tx {
+                self.write_register(Memory::RX_ADDR_P0, &addr)?;
+                self.pipe0_holds_tx = false;
+            }
+        }
+        let config = self.config_base;
+        self.config_register(
+            Memory::CONFIG,
+            &(config | ((1 << BitMnemonic::PWR_UP) | (1 << BitMnemonic::PRIM_RX))),
+        )?;
+        self.wait_power_up();
+        self.set_ce_high()?;
+        self.delay.delay_us(TSTBY2A_US);
+        Ok(())
+    }
+
+    fn power_up_tx(&mut self) -> Result<(), Error<E>> {
+        self.tx_power_status = true;
+        let config = self.config_base;
+        self.config_register(
+            Memory::CONFIG,
+            &(config | ((1 << BitMnemonic::PWR_UP) | (0 << BitMnemonic::PRIM_RX))),
+        )?;
+        self.wait_power_up();
+        Ok(())
+    }
+
+    /// Delay for `us` microseconds, beyond what a single `DelayUs<u16>` call covers
+    fn wait_us(&mut self, mut us: u32) {
+        while us > 0 {
+            let step = us.min(u16::MAX as u32) as u16;
+            self.delay.delay_us(step);
+            us -= step as u32;
+        }
+    }
+
+    fn wait_power_up(&mut self) {
+        if !self.powered_up {
+            self.delay.delay_us(TPD2STBY_US);
+            self.powered_up = true;
+        }
+    }
+
+    fn flush_rx(&mut self) -> Result<(), Error<E>> {
+        self.transaction(&mut [Instruction::FLUSH_RX])
+    }
+
+    fn flush_tx(&mut self) -> Result<(), Error<E>> {
+        self.transaction(&mut [Instruction::FLUSH_TX])
+    }
+
+    /// Empty the RX FIFO and clear RX_DR, e.g. after a protocol resync.
+    ///
+    /// Flushing alone leaves RX_DR set, which keeps the IRQ line asserted.
+    pub fn reset_rx(&mut self) -> Result<(), Error<E>> {
+        self.flush_rx()?;
+        self.config_register(Memory::STATUS, &(1 << BitMnemonic::RX_DR))
+    }
+
+    /// Temporarily borrow the SPI bus, e.g. to talk to another device on it.
+    ///
+    /// CSN is always high in between driver calls, so the radio ignores this traffic. If `f`
+    /// changes the bus mode or speed, it must restore `MODE` before returning.
+    pub fn with_spi<R>(&mut self, f: impl FnOnce(&mut SPI) -> R) -> R {
+        f(&mut self.spi)
+    }
+
+    /// Set the receive address of pipe 1, which must be `MIRF_ADDR_LEN` bytes long
+    #[deprecated(note = "use `set_pipe_address(Pipe::P1, addr)`")]
+    pub fn set_raddr(&mut self, addr: impl AsRef<[u8]>) -> Result<(), Error<E>> {
+        let addr = addr.as_ref();
+        Self::check_address(addr)?;
+        self.write_register(Memory::RX_ADDR_P1, addr)
+    }
+
+    /// Set the transmit address, and pipe 0 for the auto-ack, `MIRF_ADDR_LEN` bytes long.
+    ///
+    /// Like every address register, `addr[0]` is the least significant byte, which goes on air
+    /// first. See `set_tx_address_be` for addresses written most significant byte first.
+    pub fn set_taddr(&mut self, addr: impl AsRef<[u8]>) -> Result<(), Error<E>> {
+        let addr = addr.as_ref();
+        Self::check_address(addr)?;
+        self.tx_address.copy_from_slice(addr);
+        // With an RX address in pipe 0, `pipe0_to_tx` swaps the new address in at the next send
+        if self.pipe0_address.is_none() || self.pipe0_holds_tx {
+            self.write_register(Memory::RX_ADDR_P0, addr)?;
+            self.pipe0_holds_tx = true;
+        }
+        self.write_register(Memory::TX_ADDR, addr)?;
+        Ok(())
+    }
+
+    /// Set the transmit address given least significant byte first, the chip's own order.
+    /// Same as `set_taddr`.
+    pub fn set_tx_address_le(&mut self, addr: impl AsRef<[u8]>) -> Result<(), Error<E>> {
+        self.set_taddr(addr)
+    }
+
+    /// Set the transmit address given most significant byte first, as e.g. `0xE7E7E7E7E7` is
+    /// usually written. The bytes are reversed before going to TX_ADDR and pipe 0.
+    pub fn set_tx_address_be(&mut self, addr: impl AsRef<[u8]>) -> Result<(), Error<E>> {
+        let addr = addr.as_ref();
+        Self::check_address(addr)?;
+        let mut reversed = [0; MIRF_ADDR_LEN as usize];
+        reversed.copy_from_slice(addr);
+        reversed.reverse();
+        self.set_taddr(reversed)
+    }
+
+    /// Read TX_ADDR back from the chip, in the same order it was passed to `set_taddr`.
+    ///
+    /// The first byte is the least significant one, which goes on air first.
+    pub fn tx_address(&mut self, buf: &mut [u8; 5]) -> Result<(), Error<E>> {
+        self.read_register_into(Memory::TX_ADDR, buf)
+    }
+
+    /// Check that RX_ADDR_P0 equals TX_ADDR on the chip, if auto-ack is enabled on pipe 0.
+    ///
+    /// In Enhanced ShockBurst the receiver sends its ACK to the TX address, and the PTX
+    /// receives it on pipe 0, so a mismatch makes every send end in MAX_RT although the packets
+    /// arrive. With `set_pipe0_rx_address` pipe 0 holds the RX address while listening and
+    /// `send` swaps it, so only a check while transmitting is meaningful then.
+    pub fn verify_ack_path(&mut self) -> Result<bool, Error<E>> {
+        if self.read_register(Memory::EN_AA)? & (1 << BitMnemonic::ENAA_P0) == 0 {
+            return Ok(true);
+        }
+        let mut pipe0 = [0; MIRF_ADDR_LEN as usize];
+        let mut tx = [0; MIRF_ADDR_LEN as usize];
+        self.read_register_into(Memory::RX_ADDR_P0, &mut pipe0)?;
+        self.read_register_into(Memory::TX_ADDR, &mut tx)?;
+        Ok(pipe0 == tx)
+    }
+
+    /// Receive on pipe 0 with `addr`, which must be `MIRF_ADDR_LEN` bytes long.
+    ///
+    /// Auto-ack needs pipe 0 to hold the TX address while transmitting, so `send` swaps the TX
+    /// address into RX_ADDR_P0 right before loading the payload, and `addr` is written back
+    /// whenever the radio returns to RX (`is_sending` seeing the transmit complete, or `config`).
+    ///
+    /// The driver tracks which address pipe 0 currently holds and only swaps when the role
+    /// actually changes, so back to back sends (or RX entries) save one 6 byte register write
+    /// each.
+    pub fn set_pipe0_rx_address(&mut self, addr: impl AsRef<[u8]>) -> Result<(), Error<E>> {
+        let addr = addr.as_ref();
+        Self::check_address(addr)?;
+        let mut pipe0_address = [0; MIRF_ADDR_LEN as usize];
+        pipe0_address.copy_from_slice(addr);
+        self.pipe0_address = Some(pipe0_address);
+        if !self.tx_power_status {
+            self.write_register(Memory::RX_ADDR_P0, addr)?;
+            self.pipe0_holds_tx = false;
+        }
+        Ok(())
+    }
+
+    /// Set the receive address of `pipe`. Pipes 0 and 1 take a full `MIRF_ADDR_LEN` byte
+    /// address, pipes 2 to 5 a single byte replacing the first (least significant) byte of
+    /// pipe 1's address.
+    ///
+    /// A PTX only needs `set_taddr`, which also points pipe 0 at the TX address for the
+    /// auto-ack. A PRX sets the addresses of the pipes it listens on here; pipe 0 goes through
+    /// `set_pipe0_rx_address`, so it is restored after the node transmits.
+    pub fn set_pipe_address(&mut self, pipe: Pipe, addr: impl AsRef<[u8]>) -> Result<(), Error<E>> {
+        let addr = addr.as_ref();
+        match pipe {
+            Pipe::P0 => self.set_pipe0_rx_address(addr),
+            Pipe::P1 => {
+                Self::check_address(addr)?;
+                self.write_register(Memory::RX_ADDR_P1, addr)
+            }
+            _ => {
+                if addr.len() != 1 {
+                    return Err(Error::InvalidArgument);
+                }
+                self.write_register(Memory::RX_ADDR_P0 + pipe.number(), addr)
+            }
+        }
+    }
+
+    /// Listen on the network with address `base` on pipe 1, plus one pipe (2 to 5) for every
+    /// byte in `pipes`. Pipes 2 to 5 share the upper bytes of `base` and only differ in the
+    /// first (least significant) byte, given by `pipes`.
+    ///
+    /// All registers are written with CE low. Networks sharing a channel and data rate still
+    /// hear each other's packets on air, the addresses only filter what ends up in the FIFO.
+    pub fn set_network(&mut self, base: &[u8], pipes: &[u8]) -> Result<(), Error<E>> {
+        Self::check_address(base)?;
+        if pipes.len() > 4 {
+            return Err(Error::InvalidArgument);
+        }
+        for (i, lsb) in pipes.iter().enumerate() {
+            if *lsb == base[0] || pipes[i + 1..].contains(lsb) {
+                return Err(Error::InvalidArgument);
+            }
+        }
+
+        self.with_ce_low(|nrf24l01| {
+            nrf24l01.write_register(Memory::RX_ADDR_P1, base)?;
+            let mut enabled = (1 << BitMnemonic::ERX_P0) | (1 << BitMnemonic::ERX_P1);
+            let mut dynamic = (1 << BitMnemonic::DPL_P0) | (1 << BitMnemonic::DPL_P1);
+            for (i, lsb) in pipes.iter().enumerate() {
+                let pipe = 2 + i as u8;
+                nrf24l01.config_register(Memory::RX_ADDR_P2 + i as u8, lsb)?;
+                if !nrf24l01.using_dynamic_payload() {
+                    let payload_size = nrf24l01.payload_size;
+                    nrf24l01.config_register(Memory::RX_PW_P2 + i as u8, &payload_size)?;
+                }
+                enabled |= 1 << pipe;
+                dynamic |= 1 << pipe;
+            }
+            if nrf24l01.using_dynamic_payload() {
+                nrf24l01.config_register(Memory::DYN_PD, &dynamic)?;
+            }
+            nrf24l01.config_register(Memory::EN_RXADDR, &enabled)
+        })
+    }
+
+    /// Enable all six RX pipes in EN_RXADDR, e.g. for a gateway listening to several nodes.
+    ///
+    /// A pipe only receives with a matching address (see `set_pipe_address`) and, with static
+    /// payloads, a matching RX_PW_Px width. Pipes 2 to 5 share the upper address bytes of
+    /// pipe 1.
+    pub fn enable_all_pipes(&mut self) -> Result<(), Error<E>> {
+        self.config_register(Memory::EN_RXADDR, &0x3F)
+    }
+
+    /// Disable all RX pipes in EN_RXADDR, nothing is received until a pipe is enabled again
+    pub fn disable_all_pipes(&mut self) -> Result<(), Error<E>> {
+        self.config_register(Memory::EN_RXADDR, &0x00)
+    }
+
+    fn check_address(addr: &[u8]) -> Result<(), Error<E>> {
+        if addr.len() != MIRF_ADDR_LEN as usize {
+            return Err(Error::InvalidArgument);
+        }
+        Ok(())
+    }
+
+    /// Read STATUS as a register. `status_fast` does the same in a single byte.
+    pub fn get_status(&mut self) -> Result<u8, Error<E>> {
+        let response = self.read_register(Memory::STATUS)?;
+        Ok(response)
+    }
+
+    /// Read STATUS by sending a NOP, which clocks out the status byte in one transfer
+    pub fn status_fast(&mut self) -> Result<u8, Error<E>> {
+        self.transaction(&mut [Instruction::NOP])?;
+        Ok(self.last_status.0)
+    }
+
+    /// Quick liveness probe: read STATUS with a NOP and return `Error::NotConnected` if it is
+    /// 0x00 or 0xFF, what a floating or shorted MISO line reads.
+    ///
+    /// A healthy chip has the reserved bit 7 clear and, with an empty RX FIFO, RX_P_NO set to
+    /// 0b111, so e.g. the reset value 0x0E passes. 0x00 would need a pipe 0 packet waiting with
+    /// RX_DR cleared, which the driver avoids.
+    pub fn health_check(&mut self) -> Result<(), Error<E>> {
+        match self.status_fast()? {
+            0x00 | 0xFF => Err(Error::NotConnected),
+            _ => Ok(()),
+        }
+    }
+
+    /// Transmit `data`.
+    ///
+    /// After `power_down` the radio is powered up again first, waiting 1.5ms for the crystal
+    /// to start up. Payloads longer than `MAX_PAYLOAD` are rejected with
+    /// `Error::InvalidArgument`.
+    pub fn send(&mut self, data: &[u8]) -> Result<(), Error<E>> {
+        let mut buffer = [0; MAX_PAYLOAD + 1];
+        self.send_with_scratch(data, &mut buffer)
+    }
+
+    /// Like `send`, but builds the SPI frame in `scratch` instead of a 33 byte stack buffer.
+    ///
+    /// `scratch` must hold at least `data.len() + 1` bytes. Prefer this over `send` on targets
+    /// with very small stacks, where a long lived buffer can be reused for every transmit.
+    pub fn send_with_scratch(&mut self, data: &[u8], scratch: &mut [u8]) -> Result<(), Error<E>> {
+        self.transmit(Instruction::W_TX_PAYLOAD, data, scratch)
+    }
+
+    /// Transmit `data` without asking for an ACK, so it is sent exactly once even with
+    /// auto-ack enabled.
+    ///
+    /// W_TX_PAYLOAD_NOACK needs EN_DYN_ACK in FEATURE; without it the chip treats the command
+    /// as a normal W_TX_PAYLOAD and expects an ACK after all. EN_DYN_ACK is set here if it
+    /// isn't yet, and `Error::VerificationFailed` for FEATURE is returned if it doesn't stick,
+    /// even after ACTIVATE.
+    pub fn send_no_ack(&mut self, data: &[u8]) -> Result<(), Error<E>> {
+        if self.feature & (1 << BitMnemonic::EN_DYN_ACK) == 0 {
+            self.feature |= 1 << BitMnemonic::EN_DYN_ACK;
+            self.write_feature()?;
+        }
+        if self.read_register(Memory::FEATURE)? & (1 << BitMnemonic::EN_DYN_ACK) == 0 {
+            return Err(Error::VerificationFailed {
+                register: Memory::FEATURE,
+            });
+        }
+        let mut buffer = [0; MAX_PAYLOAD + 1];
+        self.transmit(Instruction::W_TX_PAYLOAD_NOACK, data, &mut buffer)
+    }
+
+    /// Load `data` with the payload `command` and start transmitting, see `send_with_scratch`
+    fn transmit(&mut self, command: u8, data: &[u8], scratch: &mut [u8]) -> Result<(), Error<E>> {
+        if data.len() > MAX_PAYLOAD || scratch.len() <= data.len() {
+            return Err(Error::InvalidArgument);
+        }
+        while self.tx_power_status {
+            let status = self.status_fast()?;
+            if (status & ((1 << BitMnemonic::TX_DS) | (1 << BitMnemonic::MAX_RT))) != 0 {
+                self.tx_power_status = false;
+                break;
+            }
+        }
+
+        self.set_ce_low()?;
+        self.power_up_tx()?;
+        self.pipe0_to_tx()?;
+
+        self.flush_tx()?;
+        // A TX_DS or MAX_RT left over from the previous packet would end this one right away
+        // in `is_sending`, and MAX_RT blocks transmitting altogether
+        self.clear_tx_flags()?;
+
+        // Command and payload in a single transfer keeps the CSN low window short
+        scratch[0] = command;
+        scratch[1..=data.len()].copy_from_slice(data);
+        self.transaction(&mut scratch[..=data.len()])?;
+
+        self.set_ce_high()?;
+        self.delay.delay_us(TSTBY2A_US);
+        Ok(())
+    }
+
+    /// Queue `data` behind the payloads already in the TX FIFO, waiting up to `timeout_us` for
+    /// a free slot, or `Error::Timeout`.
+    ///
+    /// Unlike `send` the TX FIFO isn't flushed, so pipelined payloads are kept and go out in
+    /// order while CE stays high. A payload hitting MAX_RT stalls the FIFO until `clear_max_rt`
+    /// or a flush, so a timeout here usually means the receiver is gone.
+    pub fn send_blocking_with_space(
+        &mut self,
+        data: &[u8],
+        timeout_us: u32,
+    ) -> Result<(), Error<E>> {
+        if data.len() > MAX_PAYLOAD {
+            return Err(Error::InvalidArgument);
+        }
+        let mut waited = 0;
+        while Status(self.status_fast()?).tx_full() {
+            if waited >= timeout_us {
+                return Err(Error::Timeout);
+            }
+            self.delay.delay_us(STATUS_POLL_US);
+            waited = waited.saturating_add(STATUS_POLL_US as u32);
+        }
+
+        if !self.tx_power_status {
+            self.set_ce_low()?;
+            self.power_up_tx()?;
+            self.pipe0_to_tx()?;
+        }
+        // TX_DS of earlier payloads would make `is_sending` report this one as done
+        self.config_register(Memory::STATUS, &(1 << BitMnemonic::TX_DS))?;
+        self.queue_payload(data)
+    }
+
+    /// Lean continuous transmit path: load `data` into the TX FIFO unless it is full, keeping
+    /// CE high, and return whether it was loaded.
+    ///
+    /// The first call flushes the TX FIFO and puts the radio in TX. After that, each call
+    /// costs a one byte STATUS read and a single command plus payload write, and nothing
+    /// else: no flush, no CONFIG write, no CE pulse and no 130µs settle time, all of which
+    /// `send` pays on every packet.
+    ///
+    /// For 32 byte payloads at 2Mbps with 5 byte addresses, 2 byte CRC and no auto-ack, a
+    /// packet is 165µs on air (see `airtime_us`). At a 10MHz SCK this costs 34 bytes, about
+    /// 27µs, of bus time per call, so the air caps a burst at about 6000 packets per second as
+    /// long as the caller keeps up. `send` clocks 39 bytes (CONFIG, FLUSH_TX, the STATUS clear
+    /// and the payload, about 31µs) and waits the 130µs settle time before the packet goes out,
+    /// and returning to RX afterwards takes another 130µs and three register accesses. That is
+    /// about 460µs per packet, or 2100 packets per second. There is also no safety net:
+    ///
+    /// - TX_DS is never cleared, so the IRQ pin stays asserted during a burst.
+    /// - A packet hitting MAX_RT stalls the FIFO; this returns `Error::MaxRetries` until
+    ///   `clear_max_rt` or the next `send`.
+    /// - Transmissions aren't counted in `tx_stats`.
+    ///
+    /// End a burst by waiting for `tx_fifo_empty`, then `start_listening` or `stop_listening`.
+    pub fn fast_send(&mut self, data: &[u8]) -> Result<bool, Error<E>> {
+        if data.len() > MAX_PAYLOAD {
+            return Err(Error::InvalidArgument);
+        }
+        if !self.tx_power_status {
+            self.set_ce_low()?;
+            self.power_up_tx()?;
+            self.pipe0_to_tx()?;
+            self.flush_tx()?;
+            self.clear_tx_flags()?;
+        } else {
+            let status = Status(self.status_fast()?);
+            if status.max_retransmits() {
+                return Err(Error::MaxRetries);
+            }
+            if status.tx_full() {
+                return Ok(false);
+            }
+        }
+        self.queue_payload(data)?;
+        Ok(true)
+    }
+
+    /// Broadcast `data` `count` times (forever if `count` is 0), waiting `interval_us`
+    /// between transmissions. Blocks for the whole time, so it suits dedicated beacon nodes,
+    /// or should run in its own task.
+    ///
+    /// The payload is loaded once and repeated with REUSE_TX_PL, each beacon costing only a
+    /// CE pulse and the STATUS polls until it is out. In between, the radio idles in
+    /// standby-I (about 26µA) and draws TX current only while a packet is on air. Beacons
+    /// usually go out without auto-ack; with it, a missing ACK returns `Error::MaxRetries`.
+    /// Returns at the first error. The TX FIFO is flushed at the end.
+    ///
+    /// Needs CE under driver control, with `CeMode::TiedHigh` this fails with
+    /// `Error::InvalidArgument`.
+    pub fn beacon(&mut self, data: &[u8], interval_us: u32, count: u32) -> Result<(), Error<E>> {
+        if self.ce_mode == CeMode::TiedHigh {
+            return Err(Error::InvalidArgument);
+        }
+        self.send(data)?;
+        self.beacon_sent()?;
+        self.set_ce_low()?;
+        self.transaction(&mut [Instruction::REUSE_TX_PL])?;
+
+        let mut sent = 1;
+        while count == 0 || sent < count {
+            self.wait_us(interval_us);
+            self.set_ce_high()?;
+            self.delay.delay_us(CE_PULSE_US);
+            self.set_ce_low()?;
+            self.beacon_sent()?;
+            sent = sent.saturating_add(1);
+        }
+
+        self.flush_tx()?;
+        self.finish_tx()?;
+        Ok(())
+    }
+
+    /// Wait for a beacon to go out and clear its TX_DS
+    fn beacon_sent(&mut self) -> Result<(), Error<E>> {
+        loop {
+            let status = Status(self.status_fast()?);
+            if status.max_retransmits() {
+                self.flush_tx()?;
+                self.finish_tx()?;
+                self.clear_tx_flags()?;
+                return Err(Error::MaxRetries);
+            }
+            if status.data_sent() {
+                return self.clear_tx_flags();
+            }
+        }
+    }
+
+    /// Transmit `payloads` back to back, keeping up to three in the TX FIFO with CE held high,
+    /// and return how many were acknowledged.
+    ///
+    /// The first payload hitting MAX_RT aborts the stream: it and everything queued after it
+    /// are flushed, so the caller can resume from the returned index. Progress is read from
+    /// FIFO_STATUS, as TX_DS can't be counted: two packets completing between polls set it
+    /// once. The FIFO only reports empty, full or in between, so after a MAX_RT the count can
+    /// be one short and a resumed stream may repeat a packet, but never skips one. Afterwards
+    /// the radio is back in RX, or TX standby with `tx_only`.
+    pub fn stream(&mut self, payloads: &[&[u8]]) -> Result<usize, Error<E>> {
+        if payloads.iter().any(|data| data.len() > MAX_PAYLOAD) {
+            return Err(Error::InvalidArgument);
+        }
+        if payloads.is_empty() {
+            return Ok(0);
+        }
+        while self.is_sending()? {}
+
+        self.set_ce_low()?;
+        self.power_up_tx()?;
+        self.pipe0_to_tx()?;
+        self.flush_tx()?;
+        self.clear_tx_flags()?;
+
+        let mut loaded: usize = 0;
+        let mut acked = 0;
+        loop {
+            let (status, fifo_status) = self.snapshot()?;
+            // Anything not in the FIFO anymore was acknowledged, in between counts as two left
+            let queued = if fifo_status.tx_empty() {
+                0
+            } else if fifo_status.tx_full() {
+                3
+            } else {
+                2
+            };
+            acked = loaded.saturating_sub(queued).max(acked);
+            if status.max_retransmits() {
+                self.flush_tx()?;
+                break;
+            }
+            if loaded == payloads.len() {
+                if fifo_status.tx_empty() {
+                    break;
+                }
+            } else if !fifo_status.tx_full() {
+                self.queue_payload(payloads[loaded])?;
+                loaded += 1;
+            }
+        }
+
+        self.finish_tx()?;
+        self.clear_tx_flags()?;
+        Ok(acked)
+    }
+
+    /// Write `data` to the TX FIFO without flushing it, raising CE if it is low
+    fn queue_payload(&mut self, data: &[u8]) -> Result<(), Error<E>> {
+        let mut buffer = [0; MAX_PAYLOAD + 1];
+        buffer[0] = Instruction::W_TX_PAYLOAD;
+        buffer[1..=data.len()].copy_from_slice(data);
+        self.transaction(&mut buffer[..=data.len()])?;
+
+        if !self.ce_state {
+            self.set_ce_high()?;
+            self.delay.delay_us(TSTBY2A_US);
+        }
+        Ok(())
+    }
+
+    /// Put the TX address into pipe 0 for the auto-ack, if it holds an RX address
+    fn pipe0_to_tx(&mut self) -> Result<(), Error<E>> {
+        if self.pipe0_address.is_some() && !self.pipe0_holds_tx {
+            let tx_address = self.tx_address;
+            self.write_register(Memory::RX_ADDR_P0, &tx_address)?;
+            self.pipe0_holds_tx = true;
+        }
+        Ok(())
+    }
+
+    /// Transmit `data` to the node at `addr`.
+    ///
+    /// Waits for any transmission in progress, then points TX_ADDR and pipe 0 at `addr`,
+    /// which costs two extra address writes per call. The address is left in place afterwards,
+    /// as the auto-ack for this transmit arrives on pipe 0.
+    pub fn send_to(&mut self, addr: &[u8], data: &[u8]) -> Result<(), Error<E>> {
+        while self.is_sending()? {}
+        self.set_taddr(addr)?;
+        self.send(data)
+    }
+
+    pub fn is_sending(&mut self) -> Result<bool, Error<E>> {
+        if self.tx_power_status {
+            let status = self.status_fast()?;
+            if (status & ((1 << BitMnemonic::TX_DS) | (1 << BitMnemonic::MAX_RT))) != 0 {
+                self.finish_tx()?;
+                self.clear_tx_flags()?;
+                return Ok(false);
+            }
+
+            return Ok(true);
+        }
+        Ok(false)
+    }
+
+    /// Check on the transmission started by `send` without blocking, with a single one byte
+    /// STATUS read while it is in progress.
+    ///
+    /// `Completed` is returned once, with the radio back in RX (or TX standby with `tx_only`)
+    /// and TX_DS and MAX_RT cleared; later calls return `Idle`. After `MaxRetries` the payload
+    /// is left in the TX FIFO until the next `send`. Even a short packet takes a few hundred
+    /// microseconds until TX_DS (see `airtime_us`), so polling much faster only adds SPI traffic.
+    pub fn poll_send(&mut self) -> Result<SendState, Error<E>> {
+        if !self.tx_power_status {
+            return Ok(SendState::Idle);
+        }
+        let status = Status(self.status_fast()?);
+        let outcome = if status.data_sent() {
+            SendOutcome::Sent
+        } else if status.max_retransmits() {
+            SendOutcome::MaxRetries
+        } else {
+            return Ok(SendState::InProgress);
+        };
+        self.finish_tx()?;
+        self.clear_tx_flags()?;
+        Ok(SendState::Completed(outcome))
+    }
+
+    /// Clear the MAX_RT flag, leaving the failed payload in the TX FIFO.
+    ///
+    /// While MAX_RT is set the chip won't transmit anything, even with CE high. Once cleared,
+    /// a CE pulse sends the same payload again, e.g. after hopping to another channel.
+    pub fn clear_max_rt(&mut self) -> Result<(), Error<E>> {
+        self.config_register(Memory::STATUS, &(1 << BitMnemonic::MAX_RT))
+    }
+
+    /// Transmit `data`, retrying the whole transmit up to `max_attempts` times while it ends in
+    /// MAX_RT.
+    ///
+    /// The TX FIFO is flushed before every attempt so stale payloads don't pile up, and each
+    /// retry waits 500µs longer than the one before. The radio is back in RX afterwards.
+    pub fn send_with_retries(
+        &mut self,
+        data: &[u8],
+        max_attempts: u8,
+    ) -> Result<SendOutcome, Error<E>> {
+        if max_attempts == 0 {
+            return Err(Error::InvalidArgument);
+        }
+
+        for attempt in 1..=max_attempts {
+            self.send(data)?;
+            if self.wait_for_send()? == SendOutcome::Sent {
+                return Ok(SendOutcome::Sent);
+            }
+            if attempt < max_attempts {
+                self.delay
+                    .delay_us(RETRY_BACKOFF_US.saturating_mul(attempt as u16));
+            }
+        }
+        Ok(SendOutcome::MaxRetries)
+    }
+
+    /// Transmit `data`, wait for it to complete and go straight back to RX.
+    ///
+    /// The radio starts listening as soon as TX_DS or MAX_RT is seen: one STATUS read and
+    /// one CONFIG write later, plus the 130µs the chip needs to enter RX.
+    pub fn transmit_then_listen(&mut self, data: &[u8]) -> Result<SendOutcome, Error<E>> {
+        self.send(data)?;
+        self.wait_for_send()
+    }
+
+    /// Like `transmit_then_listen`, with the retransmit delay and count (see
+    /// `set_retransmission`) changed for this one packet only.
+    ///
+    /// Costs a SETUP_RETR read and two writes on top of the send. The previous setting is
+    /// restored even if the send fails, only a failing restore itself leaves the new one.
+    pub fn send_with_retry_config(
+        &mut self,
+        data: &[u8],
+        delay_steps: u8,
+        count: u8,
+    ) -> Result<SendOutcome, Error<E>> {
+        let previous = self.read_register(Memory::SETUP_RETR)?;
+        self.set_retransmission(delay_steps, count)?;
+        let outcome = self.transmit_then_listen(data);
+        self.config_register(Memory::SETUP_RETR, &previous)?;
+        outcome
+    }
+
+    /// Transmit `data` and return the time in microseconds until TX_DS, including the 130µs
+    /// TX settling time, or `Error::MaxRetries` if no ACK arrived.
+    ///
+    /// The time is counted in 10µs delay steps while polling STATUS, so the resolution is no
+    /// better than that and the SPI transfers themselves aren't counted; compare values taken
+    /// on the same setup, e.g. across data rates, rather than trusting them in absolute terms.
+    pub fn ping(&mut self, data: &[u8]) -> Result<u32, Error<E>> {
+        self.send(data)?;
+        let mut elapsed = TSTBY2A_US as u32;
+        loop {
+            let status = Status(self.status_fast()?);
+            if status.data_sent() || status.max_retransmits() {
+                self.finish_tx()?;
+                self.clear_tx_flags()?;
+                return if status.data_sent() {
+                    Ok(elapsed)
+                } else {
+                    Err(Error::MaxRetries)
+                };
+            }
+            self.delay.delay_us(STATUS_POLL_US);
+            elapsed += STATUS_POLL_US as u32;
+        }
+    }
+
+    /// Transmit `data` and read the payload of the returned ACK into `ack_buf`.
+    ///
+    /// Returns the ACK payload length, `None` for an empty ACK or `Error::MaxRetries` if no ACK
+    /// arrived. Needs dynamic payloads and EN_ACK_PAY (see `set_feature`) on both ends, and the
+    /// RX FIFO should be empty beforehand so the ACK payload isn't confused with older packets.
+    pub fn send_expect_ack_payload(
+        &mut self,
+        data: &[u8],
+        ack_buf: &mut [u8],
+    ) -> Result<Option<u8>, Error<E>> {
+        self.send(data)?;
+        if self.wait_for_send()? == SendOutcome::MaxRetries {
+            return Err(Error::MaxRetries);
+        }
+        if self.rx_fifo_empty()? {
+            return Ok(None);
+        }
+        self.get_data(ack_buf).map(Some)
+    }
+
+    /// Transmit `data` and check that the ACK carries `expected_echo` as its payload.
+    ///
+    /// This is an end-to-end confirmation on top of the link layer: a hardware ACK only means
+    /// the chip received the packet, while the echo shows the application handled it. Both
+    /// ends have to agree on the echo, and the receiver must have loaded it with
+    /// `write_ack_payload` before the packet arrives. Same requirements as
+    /// `send_expect_ack_payload`; an empty ACK or a different payload returns `false`.
+    pub fn send_confirmed(&mut self, data: &[u8], expected_echo: &[u8]) -> Result<bool, Error<E>> {
+        let mut ack = payload_buffer();
+        Ok(match self.send_expect_ack_payload(data, &mut ack)? {
+            Some(len) => &ack[..len as usize] == expected_echo,
+            None => false,
+        })
+    }
+
+    /// Busy wait until the current transmission completes, then return to RX
+    fn wait_for_send(&mut self) -> Result<SendOutcome, Error<E>> {
+        loop {
+            let status = self.status_fast()?;
+            let outcome = if status & (1 << BitMnemonic::TX_DS) != 0 {
+                SendOutcome::Sent
+            } else if status & (1 << BitMnemonic::MAX_RT) != 0 {
+                SendOutcome::MaxRetries
+            } else {
+                continue;
+            };
+            self.finish_tx()?;
+            self.clear_tx_flags()?;
+            return Ok(outcome);
+        }
+    }
+
+    pub fn data_ready(&mut self) -> Result<bool, Error<E>> {
+        let status = self.status_fast()?;
+        if (status & (1 << BitMnemonic::RX_DR)) != 0 {
+            return Ok(true);
+        }
+        let fifo_empty = self.rx_fifo_empty()?;
+        Ok(!fifo_empty)
+    }
+
+    /// The pipe of the packet at the top of the RX FIFO, or `None` if the FIFO is empty
+    pub fn data_ready_pipe(&mut self) -> Result<Option<Pipe>, Error<E>> {
+        let status = self.status_fast()?;
+        Ok(Pipe::from_status(status))
+    }
+
+    fn rx_fifo_empty(&mut self) -> Result<bool, Error<E>> {
+        let fifo_status = self.read_register(Memory::FIFO_STATUS)?;
+        if fifo_status & (1 << BitMnemonic::RX_EMPTY) != 0 {
+            return Ok(true);
+        }
+        Ok(false)
+    }
+
+    /// Read STATUS and FIFO_STATUS together. STATUS is clocked out alongside the FIFO_STATUS
+    /// read command, so this costs a single two byte transfer.
+    pub fn snapshot(&mut self) -> Result<(Status, FifoStatus), Error<E>> {
+        let mut buffer = [
+            Instruction::R_REGISTER | (Instruction::REGISTER_MASK & Memory::FIFO_STATUS),
+            Instruction::NOP,
+        ];
+        self.transaction(&mut buffer)?;
+        Self::check_status(buffer[0])?;
+        Ok((Status(buffer[0]), FifoStatus(buffer[1])))
+    }
+
+    /// How full the RX FIFO is, from FIFO_STATUS.
+    ///
+    /// The chip only reports empty and full, so `Partial` is one or two packets. There is no
+    /// byte count either: with static payloads each packet is `payload_size` bytes, with
+    /// dynamic ones only the width of the top packet can be read, see `peek_rx_width`.
+    pub fn rx_fifo_occupancy(&mut self) -> Result<FifoOccupancy, Error<E>> {
+        Ok(FifoStatus(self.read_register(Memory::FIFO_STATUS)?).rx_occupancy())
+    }
+
+    /// True once every payload in the TX FIFO has been sent, i.e. it's safe to power down
+    pub fn tx_fifo_empty(&mut self) -> Result<bool, Error<E>> {
+        let fifo_status = self.read_register(Memory::FIFO_STATUS)?;
+        Ok(fifo_status & (1 << BitMnemonic::TX_EMPTY) != 0)
+    }
+
+    /// Read the width of the packet at the top of the RX FIFO without consuming it.
+    ///
+    /// This is the only non-destructive look at the RX FIFO the chip offers: R_RX_PAYLOAD
+    /// always pops the packet, so a payload can't be read twice. It needs EN_DPL, with static
+    /// payloads the width is simply `payload_size`.
+    ///
+    /// The raw value is returned as is. Per the datasheet a width above `MAX_PAYLOAD` means the
+    /// FIFO is corrupt and must be flushed.
+    pub fn peek_rx_width(&mut self) -> Result<u8, Error<E>> {
+        let mut buffer = [Instruction::R_RX_PL_WID, Instruction::NOP];
+        self.transaction(&mut buffer)?;
+        Ok(buffer[1])
+    }
+
+    /// Read the packet at the top of the RX FIFO into `buf`, returning its length.
+    ///
+    /// The packet is removed from the FIFO, the chip can't read it again. To size `buf` first,
+    /// see `peek_rx_width`. If `buf` is too short, `Error::BufferTooSmall` is returned and the
+    /// packet is left in the FIFO. A corrupt FIFO is emptied and returns `Error::CorruptRxFifo`.
     pub fn get_data(&mut self, buf: &mut [u8]) -> Result<u8, Error<E>> {
+        let payload_length = self.read_payload(buf)?;
+
+        // RX_DR must only be cleared once the FIFO is drained, otherwise the IRQ deasserts
+        // while packets are still waiting.
+        if self.rx_fifo_empty()? {
+            self.config_register(Memory::STATUS, &(1 << BitMnemonic::RX_DR))?;
+        }
+        Ok(payload_length)
+    }
+
+    /// Read the payload at the top of the RX FIFO, leaving RX_DR alone
+    fn read_payload(&mut self, buf: &mut [u8]) -> Result<u8, Error<E>> {
         let mut payload_length = self.payload_size;
-        if (self.using_dynamic_payload()) {
-            self.csn.set_low().map_err(|_| Error::Gpio)?;
-            self.spi.write(&[Instruction::R_RX_PL_WID])?;
-            let mut buffer = [0];
-            self.spi.transfer(&mut buffer)?;
-            self.csn.set_high().map_err(|_| Error::Gpio)?;
-            payload_length = buffer[0];
+        if self.using_dynamic_payload() {
+            payload_length = self.peek_rx_width()?;
         }
 
-        self.csn.set_low().map_err(|_| Error::Gpio)?;
-        self.spi.write(&[Instruction::R_RX_PAYLOAD])?;
-        self.spi.transfer(&mut buf[0..(payload_length as usize)])?;
-        self.csn.set_high().map_err(|_| Error::Gpio)?;
-        self.config_register(Memory::STATUS, &(1 << BitMnemonic::RX_DR))?;
+        // A width above MAX_PAYLOAD means the FIFO is corrupt and has to be flushed
+        let length = payload_length as usize;
+        if length > MAX_PAYLOAD {
+            self.reset_rx()?;
+            return Err(Error::CorruptRxFifo);
+        }
+        // Checked before reading, so the packet stays in the FIFO for another try
+        if buf.len() < length {
+            return Err(Error::BufferTooSmall { needed: length });
+        }
+
+        let mut buffer = [0; MAX_PAYLOAD + 1];
+        buffer[0] = Instruction::R_RX_PAYLOAD;
+        self.transaction(&mut buffer[..=length])?;
+        buf[..length].copy_from_slice(&buffer[1..=length]);
         Ok(payload_length)
     }
 
+    /// Queue `data` as the payload of the next ACK sent on `pipe`. Needs dynamic payloads and
+    /// EN_ACK_PAY (see `set_feature`).
+    pub fn write_ack_payload(&mut self, pipe: Pipe, data: &[u8]) -> Result<(), Error<E>> {
+        if data.len() > MAX_PAYLOAD {
+            return Err(Error::InvalidArgument);
+        }
+        let mut buffer = [0; MAX_PAYLOAD + 1];
+        buffer[0] = Instruction::W_ACK_PAYLOAD | pipe.number();
+        buffer[1..=data.len()].copy_from_slice(data);
+        self.transaction(&mut buffer[..=data.len()])
+    }
+
+    /// If a packet is waiting, read it into `req_buf`, queue `resp` as the ACK payload for its
+    /// pipe and return the request length.
+    ///
+    /// The request has already been acknowledged by the time it is read, so `resp` goes out
+    /// with the ACK of the *next* packet on that pipe. Senders should poll with a follow-up
+    /// packet to collect the response.
+    pub fn receive_and_respond(
+        &mut self,
+        req_buf: &mut [u8],
+        resp: &[u8],
+    ) -> Result<Option<u8>, Error<E>> {
+        let pipe = match self.data_ready_pipe()? {
+            Some(pipe) => pipe,
+            None => return Ok(None),
+        };
+        let length = self.get_data(req_buf)?;
+        self.write_ack_payload(pipe, resp)?;
+        Ok(Some(length))
+    }
+
+    /// True if any of RX_DR, TX_DS or MAX_RT is set, from a single one byte STATUS read.
+    ///
+    /// Nothing is cleared, so the main loop still has to handle the flags, e.g. with `on_irq`.
+    pub fn has_pending(&mut self) -> Result<bool, Error<E>> {
+        let status = self.status_fast()?;
+        Ok(status
+            & ((1 << BitMnemonic::RX_DR) | (1 << BitMnemonic::TX_DS) | (1 << BitMnemonic::MAX_RT))
+            != 0)
+    }
+
+    /// Handle an interrupt: call `handler` for every packet in the RX FIFO and for a completed
+    /// transmission, then clear the handled flags with a single STATUS write.
+    ///
+    /// RX_DR is only cleared after the FIFO has been drained, so no packet is left behind with
+    /// the IRQ line deasserted. After a transmission the radio returns to RX.
+    pub fn on_irq(&mut self, mut handler: impl FnMut(Event<'_>)) -> Result<(), Error<E>> {
+        let status = self.status_fast()?;
+        let flags = status
+            & ((1 << BitMnemonic::RX_DR) | (1 << BitMnemonic::TX_DS) | (1 << BitMnemonic::MAX_RT));
+
+        if flags & (1 << BitMnemonic::RX_DR) != 0 {
+            let mut buffer = payload_buffer();
+            while let Some(pipe) = Pipe::from_status(self.status_fast()?) {
+                let length = self.read_payload(&mut buffer)? as usize;
+                handler(Event::DataReady {
+                    pipe,
+                    data: &buffer[..length],
+                });
+            }
+        }
+        if flags & (1 << BitMnemonic::TX_DS) != 0 {
+            handler(Event::DataSent);
+        }
+        if flags & (1 << BitMnemonic::MAX_RT) != 0 {
+            handler(Event::MaxRetransmit);
+        }
+
+        if flags != 0 {
+            self.config_register(Memory::STATUS, &flags)?;
+        }
+        if self.tx_power_status && flags & !(1 << BitMnemonic::RX_DR) != 0 {
+            self.finish_tx()?;
+        }
+        Ok(())
+    }
+
+    /// Like `get_data`, but returns the received part of `buf`
+    pub fn receive_into<'a>(&mut self, buf: &'a mut [u8]) -> Result<&'a [u8], Error<E>> {
+        let length = self.get_data(buf)?;
+        Ok(&buf[..length as usize])
+    }
+
+    /// Like `get_data`, but returns the payload as a `heapless::Vec` of capacity `N`.
+    ///
+    /// A payload longer than `N` returns `Error::BufferTooSmall` and stays in the FIFO.
+    #[cfg(feature = "heapless")]
+    pub fn receive_vec<const N: usize>(&mut self) -> Result<heapless::Vec<u8, N>, Error<E>> {
+        let mut buffer = payload_buffer();
+        let length = self.get_data(&mut buffer[..N.min(MAX_PAYLOAD)])? as usize;
+        heapless::Vec::from_slice(&buffer[..length])
+            .map_err(|_| Error::BufferTooSmall { needed: length })
+    }
+
+    /// Like `get_data`, treating the first payload byte as a wrapping sequence number.
+    ///
+    /// Returns the payload length and how many sequence numbers were skipped since the previous
+    /// call, 0 for the first packet and for a repeated sequence number. Only meaningful if the
+    /// sender increments the first byte of every payload.
+    pub fn recv_tracked(&mut self, buf: &mut [u8]) -> Result<(usize, u8), Error<E>> {
+        let length = self.get_data(buf)? as usize;
+        if length == 0 {
+            return Ok((0, 0));
+        }
+
+        let sequence = buf[0];
+        let missed = match self.last_sequence {
+            Some(last) if last != sequence => sequence.wrapping_sub(last).wrapping_sub(1),
+            _ => 0,
+        };
+        self.last_sequence = Some(sequence);
+        Ok((length, missed))
+    }
+
+    /// The configured static payload size, 0 when using dynamic payloads
+    pub fn payload_size(&self) -> u8 {
+        self.payload_size
+    }
+
+    /// Set the static payload width of every pipe, `sizes[n]` going to RX_PW_Pn.
+    ///
+    /// `Some(width)` must be 1 to `MAX_PAYLOAD`. `None` writes a width of 0, which the chip
+    /// treats as an inactive pipe. Nothing is written if any width is out of range. Pipes with
+    /// dynamic payloads ignore these registers.
+    pub fn set_payload_sizes(&mut self, sizes: [Option<u8>; 6]) -> Result<(), Error<E>> {
+        if sizes
+            .iter()
+            .flatten()
+            .any(|&width| width == 0 || width as usize > MAX_PAYLOAD)
+        {
+            return Err(Error::InvalidArgument);
+        }
+        for (pipe, width) in sizes.iter().enumerate() {
+            self.config_register(Memory::RX_PW_P0 + pipe as u8, &width.unwrap_or(0))?;
+        }
+        Ok(())
+    }
+
+    /// True if dynamic payload lengths are used
+    pub fn is_dynamic_payload(&self) -> bool {
+        self.using_dynamic_payload()
+    }
+
+    /// The largest payload that can be sent: 32 bytes with dynamic payloads, the configured
+    /// size otherwise
+    pub fn max_payload(&self) -> u8 {
+        if self.using_dynamic_payload() {
+            MAX_PAYLOAD as u8
+        } else {
+            self.payload_size
+        }
+    }
+
     fn using_dynamic_payload(&self) -> bool {
         self.payload_size == 0
     }
+
+    /// Run a quick diagnostic of the chip and the SPI link.
+    ///
+    /// TX_ADDR is overwritten with test patterns during the test and restored afterwards. A
+    /// floating or stuck low MISO line fails every check without writing anything, instead of
+    /// returning `Error::NotConnected`.
+    pub fn self_test(&mut self) -> Result<SelfTestReport, Error<E>> {
+        // STATUS bit 7 set means nothing drives MISO, every register read would fail. SETUP_AW
+        // 0b00 is illegal, so MISO is stuck low and TX_ADDR must not be "restored" to zeros.
+        let present =
+            self.status_fast()? & 0x80 == 0 && self.read_register(Memory::SETUP_AW)? & 0b11 != 0;
+        if !present {
+            return Ok(SelfTestReport {
+                present: false,
+                spi_integrity: false,
+                rf_registers: false,
+            });
+        }
+
+        let mut saved = [0; MIRF_ADDR_LEN as usize];
+        self.read_register_into(Memory::TX_ADDR, &mut saved)?;
+        let mut spi_integrity = true;
+        for pattern in &[0x55, 0xAA] {
+            let written = [*pattern; MIRF_ADDR_LEN as usize];
+            let mut read = [0; MIRF_ADDR_LEN as usize];
+            self.write_register(Memory::TX_ADDR, &written)?;
+            self.read_register_into(Memory::TX_ADDR, &mut read)?;
+            spi_integrity &= read == written;
+        }
+        self.write_register(Memory::TX_ADDR, &saved)?;
+
+        let rf_ch = self.read_register(Memory::RF_CH)?;
+        let rf_setup = self.read_register(Memory::RF_SETUP)?;
+        let rf_registers = rf_ch & 0x80 == 0 && rf_setup & 0x40 == 0;
+
+        Ok(SelfTestReport {
+            present,
+            spi_integrity,
+            rf_registers,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::radio;
+
+    #[test]
+    fn config_enters_rx_on_channel() {
+        let (mut nrf24l01, chip) = radio(32);
+        nrf24l01.config().unwrap();
+        let chip = chip.borrow();
+        assert_eq!(chip.register(Memory::RF_CH), 76);
+        assert_eq!(chip.register(Memory::RX_PW_P0), 32);
+        assert_eq!(chip.register(Memory::CONFIG), MIRF_CONFIG | 0b11);
+        assert!(chip.ce);
+    }
+
+    #[test]
+    fn payloads_go_through_the_fifos() {
+        let (mut nrf24l01, chip) = radio(4);
+        nrf24l01.config().unwrap();
+        nrf24l01.send(&[1, 2, 3, 4]).unwrap();
+        assert!(!nrf24l01.is_sending().unwrap());
+        chip.borrow_mut().receive(1, &[5, 6, 7, 8]);
+
+        let mut buf = [0; 4];
+        assert_eq!(nrf24l01.get_data(&mut buf).unwrap(), 4);
+        assert_eq!(buf, [5, 6, 7, 8]);
+        let chip = chip.borrow();
+        assert_eq!(chip.sent, [[1, 2, 3, 4]]);
+        assert_eq!(chip.commands(Instruction::W_TX_PAYLOAD).count(), 1);
+        assert!(
+            chip.position(&[Instruction::FLUSH_TX]) < chip.position(&[Instruction::W_TX_PAYLOAD])
+        );
+        assert_eq!(chip.status() & (1 << BitMnemonic::RX_DR), 0);
+    }
+
+    #[test]
+    fn get_data_clears_rx_dr_once_the_fifo_is_drained() {
+        let (mut nrf24l01, chip) = radio(4);
+        nrf24l01.config().unwrap();
+        for n in 0..3 {
+            chip.borrow_mut().receive(1, &[n; 4]);
+        }
+
+        let mut buf = [0; 4];
+        for n in 0..3 {
+            nrf24l01.get_data(&mut buf).unwrap();
+            assert_eq!(buf, [n; 4]);
+        }
+        let chip = chip.borrow();
+        let clear_rx_dr = [
+            Instruction::W_REGISTER | Memory::STATUS,
+            1 << BitMnemonic::RX_DR,
+        ];
+        assert_eq!(
+            chip.commands(clear_rx_dr[0])
+                .filter(|t| **t == clear_rx_dr)
+                .count(),
+            1
+        );
+        let last_read = chip
+            .transactions
+            .iter()
+            .rposition(|t| t[0] == Instruction::R_RX_PAYLOAD)
+            .unwrap();
+        assert!(chip.position(&clear_rx_dr).unwrap() > last_read);
+    }
+
+    #[test]
+    fn pipe_from_status() {
+        // RX_P_NO 0b111 is an empty FIFO, 0b110 is unused
+        assert_eq!(Pipe::from_status(0b0000_1110), None);
+        assert_eq!(Pipe::from_status(0b0000_1100), None);
+        assert_eq!(Pipe::from_status(0b0100_0000), Some(Pipe::P0));
+        assert_eq!(Pipe::from_status(0b0111_1011), Some(Pipe::P5));
+        assert_eq!(Pipe::from_status(0b110), Some(Pipe::P3));
+    }
+
+    #[test]
+    fn power_down_keeps_crc_and_interrupt_masks() {
+        let (mut nrf24l01, chip) = radio(32);
+        nrf24l01.config().unwrap();
+        nrf24l01.set_crc(CrcMode::OneByte).unwrap();
+        nrf24l01.set_interrupt_mask(true, false, true).unwrap();
+        let kept = (1 << BitMnemonic::EN_CRC)
+            | (1 << BitMnemonic::MASK_RX_DR)
+            | (1 << BitMnemonic::MASK_MAX_RT);
+        let pwr_up = 1 << BitMnemonic::PWR_UP;
+        let prim_rx = 1 << BitMnemonic::PRIM_RX;
+
+        nrf24l01.power_down().unwrap();
+        assert_eq!(chip.borrow().register(Memory::CONFIG), kept | prim_rx);
+        nrf24l01.wake().unwrap();
+        assert_eq!(
+            chip.borrow().register(Memory::CONFIG),
+            kept | pwr_up | prim_rx
+        );
+
+        nrf24l01.power_down().unwrap();
+        nrf24l01.start_listening().unwrap();
+        assert_eq!(
+            chip.borrow().register(Memory::CONFIG),
+            kept | pwr_up | prim_rx
+        );
+    }
+
+    #[test]
+    fn take_packets_lost_rewrites_the_channel() {
+        let (mut nrf24l01, chip) = radio(32);
+        nrf24l01.config().unwrap();
+        chip.borrow_mut().registers[Memory::OBSERVE_TX as usize] = 0x93;
+        chip.borrow_mut().transactions.clear();
+
+        assert_eq!(nrf24l01.take_packets_lost().unwrap(), 9);
+        let chip = chip.borrow();
+        assert!(chip
+            .position(&[Instruction::W_REGISTER | Memory::RF_CH, 76])
+            .is_some());
+        assert_eq!(chip.register(Memory::OBSERVE_TX), 0x03);
+        assert_eq!(chip.register(Memory::RF_CH), 76);
+    }
+
+    #[test]
+    fn get_data_rejects_a_short_buffer() {
+        let (mut nrf24l01, chip) = radio(0);
+        nrf24l01.config().unwrap();
+        chip.borrow_mut().receive(1, &[0xAB; 32]);
+
+        let mut buf = [0; 10];
+        let result = nrf24l01.get_data(&mut buf);
+        assert!(matches!(result, Err(Error::BufferTooSmall { needed: 32 })));
+        let chip = chip.borrow();
+        assert_eq!(chip.commands(Instruction::R_RX_PAYLOAD).count(), 0);
+        assert_eq!(chip.rx_fifo.len(), 1);
+    }
+
+    #[test]
+    fn reset_rx_flushes_and_clears_rx_dr() {
+        let (mut nrf24l01, chip) = radio(4);
+        nrf24l01.config().unwrap();
+        chip.borrow_mut().receive(2, &[1; 4]);
+        chip.borrow_mut().transactions.clear();
+
+        nrf24l01.reset_rx().unwrap();
+        let chip = chip.borrow();
+        assert_eq!(
+            chip.transactions,
+            [
+                [Instruction::FLUSH_RX].as_ref(),
+                &[
+                    Instruction::W_REGISTER | Memory::STATUS,
+                    1 << BitMnemonic::RX_DR
+                ],
+            ]
+        );
+        assert!(chip.rx_fifo.is_empty());
+        assert_eq!(chip.status(), 0x0E);
+    }
+
+    #[test]
+    fn disabling_auto_ack_leaves_crc_alone() {
+        let (mut nrf24l01, chip) = radio(32);
+        nrf24l01.config().unwrap();
+        let config = chip.borrow().register(Memory::CONFIG);
+
+        for pipe in 0..6 {
+            nrf24l01
+                .set_auto_ack(Pipe::new(pipe).unwrap(), false)
+                .unwrap();
+        }
+        let chip = chip.borrow();
+        assert_eq!(chip.register(Memory::EN_AA), 0);
+        assert_eq!(chip.register(Memory::CONFIG), config);
+        assert_ne!(config & (1 << BitMnemonic::EN_CRC), 0);
+    }
+
+    #[test]
+    fn health_check_detects_a_dead_miso_line() {
+        let (mut nrf24l01, chip) = radio(32);
+        assert!(nrf24l01.health_check().is_ok());
+        assert_eq!(nrf24l01.last_status().0, 0x0E);
+
+        for &miso in &[0x00, 0xFF] {
+            chip.borrow_mut().miso = Some(miso);
+            assert!(matches!(nrf24l01.health_check(), Err(Error::NotConnected)));
+        }
+    }
+
+    #[test]
+    fn set_tx_address_be_reverses_the_bytes() {
+        let (mut nrf24l01, chip) = radio(32);
+        nrf24l01
+            .set_tx_address_be([0xE7, 0xD3, 0xF0, 0x35, 0x77])
+            .unwrap();
+
+        let expected = [
+            Instruction::W_REGISTER | Memory::RX_ADDR_P0,
+            0x77,
+            0x35,
+            0xF0,
+            0xD3,
+            0xE7,
+        ];
+        let chip = chip.borrow();
+        assert!(chip.position(&expected).is_some());
+        let mut expected = expected;
+        expected[0] = Instruction::W_REGISTER | Memory::TX_ADDR;
+        assert!(chip.position(&expected).is_some());
+        assert_eq!(chip.tx_addr, [0x77, 0x35, 0xF0, 0xD3, 0xE7]);
+        assert_eq!(chip.rx_addr_p0, chip.tx_addr);
+    }
+
+    #[test]
+    fn send_clears_tx_flags_before_loading_the_payload() {
+        let (mut nrf24l01, chip) = radio(4);
+        nrf24l01.config().unwrap();
+        chip.borrow_mut().registers[Memory::STATUS as usize] |= 1 << BitMnemonic::MAX_RT;
+        chip.borrow_mut().transactions.clear();
+
+        nrf24l01.send(&[1, 2, 3, 4]).unwrap();
+        let chip = chip.borrow();
+        let clear = [
+            Instruction::W_REGISTER | Memory::STATUS,
+            (1 << BitMnemonic::TX_DS) | (1 << BitMnemonic::MAX_RT),
+        ];
+        let load = chip.position(&[Instruction::W_TX_PAYLOAD]).unwrap();
+        assert!(chip.position(&clear).unwrap() < load);
+    }
+
+    #[test]
+    fn send_no_ack_sets_en_dyn_ack_first() {
+        let (mut nrf24l01, chip) = radio(4);
+        nrf24l01.config().unwrap();
+        chip.borrow_mut().transactions.clear();
+
+        nrf24l01.send_no_ack(&[1, 2, 3, 4]).unwrap();
+        let chip = chip.borrow();
+        let feature = [
+            Instruction::W_REGISTER | Memory::FEATURE,
+            1 << BitMnemonic::EN_DYN_ACK,
+        ];
+        let load = chip.position(&[Instruction::W_TX_PAYLOAD_NOACK]).unwrap();
+        assert!(chip.position(&feature).unwrap() < load);
+        assert_eq!(chip.register(Memory::FEATURE), 1 << BitMnemonic::EN_DYN_ACK);
+    }
+
+    #[test]
+    fn stream_survives_coalesced_tx_ds() {
+        let (mut nrf24l01, chip) = radio(1);
+        nrf24l01.config().unwrap();
+        // Two payloads go out between polls, setting TX_DS only once
+        chip.borrow_mut().tx_latency = 2;
+
+        let payloads: [&[u8]; 5] = [&[0], &[1], &[2], &[3], &[4]];
+        assert_eq!(nrf24l01.stream(&payloads).unwrap(), 5);
+        assert_eq!(chip.borrow().sent, payloads);
+    }
+
+    #[test]
+    fn stream_stops_at_max_rt() {
+        let (mut nrf24l01, chip) = radio(1);
+        nrf24l01.config().unwrap();
+        chip.borrow_mut().tx_fails = true;
+
+        let payloads: [&[u8]; 5] = [&[0], &[1], &[2], &[3], &[4]];
+        assert_eq!(nrf24l01.stream(&payloads).unwrap(), 0);
+        let chip = chip.borrow();
+        assert!(chip.sent.is_empty());
+        assert!(chip.tx_fifo.is_empty());
+        assert_eq!(chip.status() & 0x70, 0);
+    }
+
+    #[cfg(feature = "frag")]
+    #[test]
+    fn send_message_stops_at_max_rt() {
+        let (mut nrf24l01, chip) = radio(0);
+        nrf24l01.config().unwrap();
+        chip.borrow_mut().tx_fails = true;
+
+        let result = nrf24l01.send_message(&[0; 40]);
+        assert!(matches!(result, Err(Error::MaxRetries)));
+        assert_eq!(chip.borrow().commands(Instruction::W_TX_PAYLOAD).count(), 1);
+    }
+
+    #[cfg(feature = "frag")]
+    #[test]
+    fn recv_message_resyncs_after_a_gap() {
+        let (mut nrf24l01, chip) = radio(0);
+        nrf24l01.config().unwrap();
+        let mut buf = [0; 64];
+
+        chip.borrow_mut().receive(1, &[0, 3, 1]);
+        chip.borrow_mut().receive(1, &[2, 3, 3]);
+        let result = nrf24l01.recv_message(&mut buf);
+        assert!(matches!(result, Err(Error::MissingFragment)));
+
+        // Leftovers of a broken message are skipped, a new start replaces a broken message
+        for fragment in &[&[1, 2, 0xAA][..], &[0, 2, 0xBB], &[0, 2, 1], &[1, 2, 2]] {
+            chip.borrow_mut().receive(1, fragment);
+        }
+        assert_eq!(nrf24l01.recv_message(&mut buf).unwrap(), 2);
+        assert_eq!(buf[..2], [1, 2]);
+        assert!(chip.borrow().rx_fifo.is_empty());
+    }
+
+    #[test]
+    fn set_taddr_keeps_the_pipe0_rx_address_while_listening() {
+        let (mut nrf24l01, chip) = radio(1);
+        nrf24l01.set_pipe0_rx_address([1; 5]).unwrap();
+        nrf24l01.config().unwrap();
+        nrf24l01.set_taddr([2; 5]).unwrap();
+        assert_eq!(chip.borrow().rx_addr_p0, [1; 5]);
+        assert_eq!(chip.borrow().tx_addr, [2; 5]);
+
+        nrf24l01.send(&[0]).unwrap();
+        assert_eq!(chip.borrow().rx_addr_p0, [2; 5]);
+        assert!(!nrf24l01.is_sending().unwrap());
+        assert_eq!(chip.borrow().rx_addr_p0, [1; 5]);
+    }
+
+    #[test]
+    fn address_is_accepted_by_the_setters() {
+        let (mut nrf24l01, chip) = radio(32);
+        let address = Address::new([1, 2, 3, 4, 5]);
+        nrf24l01.set_taddr(address).unwrap();
+        nrf24l01.set_pipe_address(Pipe::P1, address).unwrap();
+        assert_eq!(chip.borrow().tx_addr, [1, 2, 3, 4, 5]);
+        assert_eq!(chip.borrow().rx_addr_p1, [1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn self_test_reports_a_missing_chip() {
+        let (mut nrf24l01, chip) = radio(32);
+        assert!(nrf24l01.self_test().unwrap().passed());
+        assert_eq!(chip.borrow().tx_addr, [0xE7; 5]);
+
+        for &miso in &[0x00, 0xFF] {
+            chip.borrow_mut().miso = Some(miso);
+            let report = nrf24l01.self_test().unwrap();
+            assert!(!report.present && !report.spi_integrity && !report.rf_registers);
+            assert_eq!(chip.borrow().tx_addr, [0xE7; 5]);
+        }
+    }
+
+    #[cfg(feature = "heapless")]
+    #[test]
+    fn receive_vec_checks_the_capacity() {
+        let (mut nrf24l01, chip) = radio(0);
+        nrf24l01.config().unwrap();
+        chip.borrow_mut().receive(1, &[7; 12]);
+
+        let result = nrf24l01.receive_vec::<8>();
+        assert!(matches!(result, Err(Error::BufferTooSmall { needed: 12 })));
+        assert_eq!(nrf24l01.receive_vec::<16>().unwrap(), [7; 12]);
+        assert!(chip.borrow().rx_fifo.is_empty());
+    }
+
+    #[test]
+    fn fast_send_flushes_only_once() {
+        let (mut nrf24l01, chip) = radio(1);
+        nrf24l01.config().unwrap();
+        // Nothing goes on air, so the FIFO fills up
+        chip.borrow_mut().tx_latency = usize::MAX;
+        chip.borrow_mut().transactions.clear();
+
+        for n in 0..3 {
+            assert!(nrf24l01.fast_send(&[n]).unwrap());
+        }
+        assert!(!nrf24l01.fast_send(&[3]).unwrap());
+        let chip = chip.borrow();
+        assert_eq!(chip.commands(Instruction::FLUSH_TX).count(), 1);
+        let first_load = chip.position(&[Instruction::W_TX_PAYLOAD]).unwrap();
+        assert!(chip.position(&[Instruction::FLUSH_TX]).unwrap() < first_load);
+        assert_eq!(chip.tx_fifo, [[0], [1], [2]]);
+    }
+
+    #[test]
+    fn fast_send_stops_at_max_rt() {
+        let (mut nrf24l01, chip) = radio(1);
+        nrf24l01.config().unwrap();
+        chip.borrow_mut().tx_fails = true;
+
+        assert!(nrf24l01.fast_send(&[0]).unwrap());
+        assert!(matches!(nrf24l01.fast_send(&[1]), Err(Error::MaxRetries)));
+        assert_eq!(chip.borrow().tx_fifo, [[0]]);
+    }
+
+    #[test]
+    fn detect_variant_probes_a_valid_rate() {
+        let (mut nrf24l01, chip) = radio(32);
+        assert_eq!(nrf24l01.detect_variant().unwrap(), ChipVariant::Plus);
+        // RF_SETUP 0x0E selects 2Mbps, the probe must not add RF_DR_LOW on top of RF_DR
+        let probe = [Instruction::W_REGISTER | Memory::RF_SETUP, 0x26];
+        assert!(chip.borrow().position(&probe).is_some());
+        assert_eq!(chip.borrow().register(Memory::RF_SETUP), 0x0E);
+
+        chip.borrow_mut().non_plus = true;
+        assert_eq!(nrf24l01.detect_variant().unwrap(), ChipVariant::NonPlus);
+        assert_eq!(nrf24l01.chip_variant(), Some(ChipVariant::NonPlus));
+    }
+
+    #[test]
+    fn new_rejects_a_channel_above_max_channel() {
+        let (spi, csn, ce, _) = crate::mock::parts();
+        let result = NRF24L01::new(spi, csn, ce, NoDelay, MAX_CHANNEL + 1, 32);
+        assert!(matches!(result, Err(Error::InvalidArgument)));
+
+        let (spi, csn, ce, _) = crate::mock::parts();
+        assert!(NRF24L01::new(spi, csn, ce, NoDelay, MAX_CHANNEL, 32).is_ok());
+    }
+
+    #[test]
+    fn new_rejects_a_payload_size_above_max_payload() {
+        let (spi, csn, ce, _) = crate::mock::parts();
+        let result = NRF24L01::new(spi, csn, ce, NoDelay, 76, MAX_PAYLOAD as u8 + 1);
+        assert!(matches!(result, Err(Error::InvalidArgument)));
+    }
+
+    #[test]
+    fn get_data_resets_a_corrupt_rx_fifo() {
+        let (mut nrf24l01, chip) = radio(0);
+        nrf24l01.config().unwrap();
+        chip.borrow_mut().receive(1, &[0; MAX_PAYLOAD + 1]);
+
+        let mut buf = payload_buffer();
+        assert!(matches!(
+            nrf24l01.get_data(&mut buf),
+            Err(Error::CorruptRxFifo)
+        ));
+        assert!(chip.borrow().rx_fifo.is_empty());
+        assert_eq!(chip.borrow().status() & (1 << BitMnemonic::RX_DR), 0);
+        assert!(!nrf24l01.data_ready().unwrap());
+    }
+
+    #[test]
+    fn channel_activity_restores_the_channel() {
+        let (mut nrf24l01, chip) = radio(32);
+        nrf24l01.config().unwrap();
+        let config = chip.borrow().register(Memory::CONFIG);
+        chip.borrow_mut().registers[Memory::RPD as usize] = 1;
+
+        assert!(nrf24l01
+            .channel_activity(Channel::new(10).unwrap())
+            .unwrap());
+        assert!(chip
+            .borrow()
+            .position(&[Instruction::W_REGISTER | Memory::RF_CH, 10])
+            .is_some());
+        assert_eq!(chip.borrow().register(Memory::RF_CH), 76);
+        assert_eq!(chip.borrow().register(Memory::CONFIG), config);
+        assert!(chip.borrow().ce);
+
+        // Also when reading RPD fails
+        chip.borrow_mut().fail_command = Some(Instruction::R_REGISTER | Memory::RPD);
+        assert!(nrf24l01
+            .channel_activity(Channel::new(10).unwrap())
+            .is_err());
+        assert_eq!(chip.borrow().register(Memory::RF_CH), 76);
+        assert_eq!(nrf24l01.channel(), 76);
+        assert_eq!(chip.borrow().register(Memory::CONFIG), config);
+    }
 }
@@ -0,0 +1,303 @@
+//! In-memory model of the chip behind the SPI bus, CSN and CE, for the unit tests.
+//!
+//! Registers, addresses and both FIFOs are modelled closely enough to run the driver against,
+//! and every CSN low window is logged with the bytes sent to the chip.
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::rc::Rc;
+use std::vec::Vec;
+
+use embedded_hal::blocking::spi::{Transfer, Write};
+use embedded_hal::digital::v2::OutputPin;
+
+use crate::{BitMnemonic, Instruction, Memory, NoDelay, MAX_PAYLOAD, NRF24L01};
+
+pub type Radio = NRF24L01<MockSpi, MockPin, MockPin, NoDelay>;
+
+pub struct Chip {
+    pub registers: [u8; 0x20],
+    pub rx_addr_p0: [u8; 5],
+    pub rx_addr_p1: [u8; 5],
+    pub tx_addr: [u8; 5],
+    pub tx_fifo: VecDeque<Vec<u8>>,
+    /// Pipe and payload of every received packet
+    pub rx_fifo: VecDeque<(u8, Vec<u8>)>,
+    /// Payloads that went on air, in order
+    pub sent: Vec<Vec<u8>>,
+    /// The bytes sent to the chip in every CSN low window, in order
+    pub transactions: Vec<Vec<u8>>,
+    /// Drive every MISO byte with this value, as a missing chip would
+    pub miso: Option<u8>,
+    /// End every transmission in MAX_RT
+    pub tx_fails: bool,
+    /// Number of transactions with CE high before the TX FIFO goes on air. All queued
+    /// payloads are sent together and TX_DS is set once, as when polling is too slow.
+    pub tx_latency: usize,
+    /// Behave like an nRF24L01 without the plus, on which RF_DR_LOW can't be set
+    pub non_plus: bool,
+    /// Fail the next SPI transfer of a transaction with this command byte
+    pub fail_command: Option<u8>,
+    pub ce: bool,
+    selected: bool,
+    air_time: usize,
+}
+
+impl Chip {
+    fn new() -> Self {
+        let mut registers = [0; 0x20];
+        registers[Memory::CONFIG as usize] = 0x08;
+        registers[Memory::EN_AA as usize] = 0x3F;
+        registers[Memory::EN_RXADDR as usize] = 0x03;
+        registers[Memory::SETUP_AW as usize] = 0x03;
+        registers[Memory::SETUP_RETR as usize] = 0x03;
+        registers[Memory::RF_CH as usize] = 0x02;
+        registers[Memory::RF_SETUP as usize] = 0x0E;
+        registers[Memory::RX_ADDR_P2 as usize] = 0xC3;
+        registers[Memory::RX_ADDR_P3 as usize] = 0xC4;
+        registers[Memory::RX_ADDR_P4 as usize] = 0xC5;
+        registers[Memory::RX_ADDR_P5 as usize] = 0xC6;
+        Chip {
+            registers,
+            rx_addr_p0: [0xE7; 5],
+            rx_addr_p1: [0xC2; 5],
+            tx_addr: [0xE7; 5],
+            tx_fifo: VecDeque::new(),
+            rx_fifo: VecDeque::new(),
+            sent: Vec::new(),
+            transactions: Vec::new(),
+            miso: None,
+            tx_fails: false,
+            tx_latency: 0,
+            non_plus: false,
+            fail_command: None,
+            ce: false,
+            selected: false,
+            air_time: 0,
+        }
+    }
+
+    /// Put a packet into the RX FIFO as if it arrived on `pipe`, setting RX_DR
+    pub fn receive(&mut self, pipe: u8, data: &[u8]) {
+        self.rx_fifo.push_back((pipe, data.to_vec()));
+        self.registers[Memory::STATUS as usize] |= 1 << BitMnemonic::RX_DR;
+    }
+
+    pub fn register(&self, register: u8) -> u8 {
+        self.registers[register as usize]
+    }
+
+    pub fn status(&self) -> u8 {
+        let rx_pipe = self.rx_fifo.front().map_or(0b111, |(pipe, _)| *pipe);
+        let tx_full = (self.tx_fifo.len() == 3) as u8;
+        (self.registers[Memory::STATUS as usize] & 0x70) | (rx_pipe << 1) | tx_full
+    }
+
+    pub fn fifo_status(&self) -> u8 {
+        ((self.tx_fifo.len() == 3) as u8) << BitMnemonic::FIFO_FULL
+            | (self.tx_fifo.is_empty() as u8) << BitMnemonic::TX_EMPTY
+            | ((self.rx_fifo.len() == 3) as u8) << BitMnemonic::RX_FULL
+            | (self.rx_fifo.is_empty() as u8) << BitMnemonic::RX_EMPTY
+    }
+
+    /// Transactions whose command byte is `command`
+    pub fn commands(&self, command: u8) -> impl Iterator<Item = &Vec<u8>> {
+        self.transactions.iter().filter(move |t| t[0] == command)
+    }
+
+    /// Index of the first transaction starting with `prefix`
+    pub fn position(&self, prefix: &[u8]) -> Option<usize> {
+        self.transactions.iter().position(|t| t.starts_with(prefix))
+    }
+
+    fn select(&mut self) {
+        self.selected = true;
+        self.transactions.push(Vec::new());
+        self.run_tx();
+    }
+
+    fn run_tx(&mut self) {
+        let config = self.registers[Memory::CONFIG as usize];
+        let transmitting = self.ce
+            && config & (1 << BitMnemonic::PWR_UP) != 0
+            && config & (1 << BitMnemonic::PRIM_RX) == 0
+            && self.registers[Memory::STATUS as usize] & (1 << BitMnemonic::MAX_RT) == 0
+            && !self.tx_fifo.is_empty();
+        if !transmitting {
+            return;
+        }
+        if self.air_time < self.tx_latency {
+            self.air_time += 1;
+            return;
+        }
+        self.air_time = 0;
+        if self.tx_fails {
+            self.registers[Memory::STATUS as usize] |= 1 << BitMnemonic::MAX_RT;
+        } else {
+            self.sent.extend(self.tx_fifo.drain(..));
+            self.registers[Memory::STATUS as usize] |= 1 << BitMnemonic::TX_DS;
+        }
+    }
+
+    fn exchange(&mut self, mosi: u8) -> u8 {
+        let transaction = self.transactions.last_mut().unwrap();
+        let index = transaction.len();
+        transaction.push(mosi);
+        let command = transaction[0];
+        if let Some(miso) = self.miso {
+            return miso;
+        }
+        if index == 0 {
+            return self.status();
+        }
+        let offset = index - 1;
+        match command {
+            0x00..=0x1F => match command {
+                Memory::RX_ADDR_P0 => self.rx_addr_p0.get(offset).copied().unwrap_or(0),
+                Memory::RX_ADDR_P1 => self.rx_addr_p1.get(offset).copied().unwrap_or(0),
+                Memory::TX_ADDR => self.tx_addr.get(offset).copied().unwrap_or(0),
+                Memory::STATUS if offset == 0 => self.status(),
+                Memory::FIFO_STATUS if offset == 0 => self.fifo_status(),
+                register if offset == 0 => self.registers[register as usize],
+                _ => 0,
+            },
+            Instruction::R_RX_PAYLOAD => self
+                .rx_fifo
+                .front()
+                .and_then(|(_, data)| data.get(offset).copied())
+                .unwrap_or(0),
+            Instruction::R_RX_PL_WID => {
+                self.rx_fifo.front().map_or(0, |(_, data)| data.len() as u8)
+            }
+            _ => 0,
+        }
+    }
+
+    fn deselect(&mut self) {
+        self.selected = false;
+        let transaction = self.transactions.last().unwrap().clone();
+        let (command, data) = match transaction.split_first() {
+            Some(split) => split,
+            None => return,
+        };
+        match *command {
+            0x20..=0x3F if !data.is_empty() => self.write(command & 0x1F, data),
+            Instruction::R_RX_PAYLOAD if !data.is_empty() => {
+                self.rx_fifo.pop_front();
+            }
+            Instruction::W_TX_PAYLOAD | Instruction::W_TX_PAYLOAD_NOACK
+                if self.tx_fifo.len() < 3 && data.len() <= MAX_PAYLOAD =>
+            {
+                self.tx_fifo.push_back(data.to_vec());
+            }
+            Instruction::FLUSH_TX => self.tx_fifo.clear(),
+            Instruction::FLUSH_RX => self.rx_fifo.clear(),
+            _ => {}
+        }
+    }
+
+    fn write(&mut self, register: u8, data: &[u8]) {
+        let address = |target: &mut [u8; 5]| {
+            let len = data.len().min(5);
+            target[..len].copy_from_slice(&data[..len]);
+        };
+        match register {
+            Memory::RX_ADDR_P0 => address(&mut self.rx_addr_p0),
+            Memory::RX_ADDR_P1 => address(&mut self.rx_addr_p1),
+            Memory::TX_ADDR => address(&mut self.tx_addr),
+            // The interrupt flags are cleared by writing 1
+            Memory::STATUS => self.registers[register as usize] &= !(data[0] & 0x70),
+            // Writing RF_CH resets PLOS_CNT
+            Memory::RF_CH => {
+                self.registers[register as usize] = data[0];
+                self.registers[Memory::OBSERVE_TX as usize] &= 0x0F;
+            }
+            Memory::RF_SETUP if self.non_plus => {
+                self.registers[register as usize] = data[0] & !(1 << BitMnemonic::RF_DR_LOW);
+            }
+            Memory::OBSERVE_TX | Memory::RPD | Memory::FIFO_STATUS => {}
+            _ => self.registers[register as usize] = data[0],
+        }
+    }
+}
+
+pub struct MockSpi(Rc<RefCell<Chip>>);
+
+impl Transfer<u8> for MockSpi {
+    type Error = ();
+
+    fn transfer<'w>(&mut self, words: &'w mut [u8]) -> Result<&'w [u8], ()> {
+        let mut chip = self.0.borrow_mut();
+        assert!(chip.selected, "SPI transfer with CSN high");
+        for word in words.iter_mut() {
+            *word = chip.exchange(*word);
+        }
+        let command = chip.transactions.last().unwrap()[0];
+        if chip.fail_command == Some(command) {
+            chip.fail_command = None;
+            return Err(());
+        }
+        Ok(words)
+    }
+}
+
+impl Write<u8> for MockSpi {
+    type Error = ();
+
+    fn write(&mut self, words: &[u8]) -> Result<(), ()> {
+        let mut chip = self.0.borrow_mut();
+        assert!(chip.selected, "SPI write with CSN high");
+        for word in words {
+            chip.exchange(*word);
+        }
+        Ok(())
+    }
+}
+
+pub enum MockPin {
+    Csn(Rc<RefCell<Chip>>),
+    Ce(Rc<RefCell<Chip>>),
+}
+
+impl OutputPin for MockPin {
+    type Error = ();
+
+    fn set_low(&mut self) -> Result<(), ()> {
+        match self {
+            MockPin::Csn(chip) => chip.borrow_mut().select(),
+            MockPin::Ce(chip) => chip.borrow_mut().ce = false,
+        }
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), ()> {
+        match self {
+            MockPin::Csn(chip) => {
+                let mut chip = chip.borrow_mut();
+                if chip.selected {
+                    chip.deselect();
+                }
+            }
+            MockPin::Ce(chip) => chip.borrow_mut().ce = true,
+        }
+        Ok(())
+    }
+}
+
+/// SPI, CSN and CE of a chip fresh out of reset
+pub fn parts() -> (MockSpi, MockPin, MockPin, Rc<RefCell<Chip>>) {
+    let chip = Rc::new(RefCell::new(Chip::new()));
+    (
+        MockSpi(chip.clone()),
+        MockPin::Csn(chip.clone()),
+        MockPin::Ce(chip.clone()),
+        chip,
+    )
+}
+
+/// A driver created with `new` on `channel` 76, attached to a chip fresh out of reset
+pub fn radio(payload_size: u8) -> (Radio, Rc<RefCell<Chip>>) {
+    let (spi, csn, ce, chip) = parts();
+    let radio = NRF24L01::new(spi, csn, ce, NoDelay, 76, payload_size).unwrap();
+    (radio, chip)
+}
@@ -0,0 +1,119 @@
+//! Applying a complete configuration and verifying it register by register
+
+use embedded_hal::blocking;
+use embedded_hal::blocking::delay::DelayUs;
+use embedded_hal::digital::v2::OutputPin;
+
+use crate::{
+    BitMnemonic, Channel, CrcMode, DataRate, Error, Memory, MAX_PAYLOAD, MIRF_ADDR_LEN, NRF24L01,
+};
+
+/// Complete radio configuration for `NRF24L01::provision`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Nrf24Config {
+    /// RF channel (RF_CH)
+    pub channel: Channel,
+    /// Air data rate
+    pub data_rate: DataRate,
+    /// Must not be `CrcMode::Disabled` while `auto_ack` has any pipe enabled
+    pub crc: CrcMode,
+    /// Bitmask of the pipes with auto-ack (EN_AA)
+    pub auto_ack: u8,
+    /// Bitmask of the enabled RX pipes (EN_RXADDR)
+    pub rx_pipes: u8,
+    /// Auto retransmit delay in 250µs steps, 0 to 15
+    pub retransmit_delay_steps: u8,
+    /// Number of retransmits, 0 to 15
+    pub retransmit_count: u8,
+    /// Static payload size of every enabled pipe, or 0 for dynamic payloads on them
+    pub payload_size: u8,
+    /// Transmit address, least significant byte first
+    pub tx_address: [u8; MIRF_ADDR_LEN as usize],
+}
+
+impl<E, SPI, CSN, CE, DELAY, IRQ> NRF24L01<SPI, CSN, CE, DELAY, IRQ>
+where
+    SPI: blocking::spi::Transfer<u8, Error = E> + blocking::spi::Write<u8, Error = E>,
+    CSN: OutputPin,
+    CE: OutputPin,
+    DELAY: DelayUs<u16>,
+{
+    /// Apply `cfg` with `config`, then read back every register written and return
+    /// `Error::VerificationFailed` for the first one that doesn't match.
+    ///
+    /// Verified are RF_CH, the data rate bits of RF_SETUP, the CRC bits of CONFIG, EN_AA,
+    /// EN_RXADDR, SETUP_RETR, TX_ADDR, FEATURE and, depending on `payload_size`, DYN_PD or the
+    /// RX_PW_Px of every enabled pipe. Meant for production tests, where an intermittent SPI
+    /// fault should fail the unit rather than show up in the field. The radio is left in RX.
+    pub fn provision(&mut self, cfg: &Nrf24Config) -> Result<(), Error<E>> {
+        if cfg.retransmit_delay_steps > 15
+            || cfg.retransmit_count > 15
+            || cfg.payload_size as usize > MAX_PAYLOAD
+            || (cfg.crc == CrcMode::Disabled && cfg.auto_ack & 0x3F != 0)
+        {
+            return Err(Error::InvalidArgument);
+        }
+        let auto_ack = cfg.auto_ack & 0x3F;
+        let rx_pipes = cfg.rx_pipes & 0x3F;
+
+        self.channel = cfg.channel.number();
+        self.payload_size = cfg.payload_size;
+        self.config()?;
+        self.set_taddr(cfg.tx_address)?;
+        self.set_data_rate(cfg.data_rate)?;
+        self.set_crc(cfg.crc)?;
+        self.config_register(Memory::EN_AA, &auto_ack)?;
+        self.config_register(Memory::EN_RXADDR, &rx_pipes)?;
+        self.set_retransmission(cfg.retransmit_delay_steps, cfg.retransmit_count)?;
+        if self.using_dynamic_payload() {
+            self.config_register(Memory::DYN_PD, &rx_pipes)?;
+        } else {
+            for pipe in (0..6).filter(|pipe| rx_pipes & (1 << pipe) != 0) {
+                self.config_register(Memory::RX_PW_P0 + pipe, &cfg.payload_size)?;
+            }
+        }
+
+        let data_rate_mask = (1 << BitMnemonic::RF_DR_LOW) | (1 << BitMnemonic::RF_DR);
+        let data_rate = match cfg.data_rate {
+            DataRate::R250Kbps => 1 << BitMnemonic::RF_DR_LOW,
+            DataRate::R1Mbps => 0,
+            DataRate::R2Mbps => 1 << BitMnemonic::RF_DR,
+        };
+        let crc_mask = (1 << BitMnemonic::EN_CRC) | (1 << BitMnemonic::CRCO);
+        let crc = self.config_base & crc_mask;
+        let setup_retr = (cfg.retransmit_delay_steps << BitMnemonic::ARD)
+            | (cfg.retransmit_count << BitMnemonic::ARC);
+        let feature = self.feature;
+
+        self.verify_register(Memory::RF_CH, 0x7F, cfg.channel.number())?;
+        self.verify_register(Memory::RF_SETUP, data_rate_mask, data_rate)?;
+        self.verify_register(Memory::CONFIG, crc_mask, crc)?;
+        self.verify_register(Memory::EN_AA, 0x3F, auto_ack)?;
+        self.verify_register(Memory::EN_RXADDR, 0x3F, rx_pipes)?;
+        self.verify_register(Memory::SETUP_RETR, 0xFF, setup_retr)?;
+        self.verify_register(Memory::FEATURE, 0xFF, feature)?;
+        if self.using_dynamic_payload() {
+            self.verify_register(Memory::DYN_PD, 0x3F, rx_pipes)?;
+        } else {
+            for pipe in (0..6).filter(|pipe| rx_pipes & (1 << pipe) != 0) {
+                self.verify_register(Memory::RX_PW_P0 + pipe, 0x3F, cfg.payload_size)?;
+            }
+        }
+
+        let mut tx_address = [0; MIRF_ADDR_LEN as usize];
+        self.tx_address(&mut tx_address)?;
+        if tx_address != cfg.tx_address {
+            return Err(Error::VerificationFailed {
+                register: Memory::TX_ADDR,
+            });
+        }
+        Ok(())
+    }
+
+    fn verify_register(&mut self, register: u8, mask: u8, expected: u8) -> Result<(), Error<E>> {
+        if self.read_register(register)? & mask != expected {
+            return Err(Error::VerificationFailed { register });
+        }
+        Ok(())
+    }
+}
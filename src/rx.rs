@@ -0,0 +1,98 @@
+//! Draining the RX FIFO with an iterator
+
+use embedded_hal::blocking;
+use embedded_hal::blocking::delay::DelayUs;
+use embedded_hal::digital::v2::OutputPin;
+
+use crate::{payload_buffer, Error, Pipe, MAX_PAYLOAD, NRF24L01};
+
+/// A payload copied out of the RX FIFO
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Payload {
+    pipe: Pipe,
+    len: u8,
+    data: [u8; MAX_PAYLOAD],
+}
+
+impl Payload {
+    /// The pipe the payload was received on
+    pub fn pipe(&self) -> Pipe {
+        self.pipe
+    }
+
+    /// The payload bytes
+    pub fn data(&self) -> &[u8] {
+        &self.data[..self.len as usize]
+    }
+}
+
+impl AsRef<[u8]> for Payload {
+    fn as_ref(&self) -> &[u8] {
+        self.data()
+    }
+}
+
+/// Iterator over the payloads in the RX FIFO, see `NRF24L01::rx_iter`
+pub struct RxIter<'a, SPI, CSN, CE, DELAY, IRQ> {
+    nrf24l01: &'a mut NRF24L01<SPI, CSN, CE, DELAY, IRQ>,
+    done: bool,
+}
+
+impl<'a, E, SPI, CSN, CE, DELAY, IRQ> Iterator for RxIter<'a, SPI, CSN, CE, DELAY, IRQ>
+where
+    SPI: blocking::spi::Transfer<u8, Error = E> + blocking::spi::Write<u8, Error = E>,
+    CSN: OutputPin,
+    CE: OutputPin,
+    DELAY: DelayUs<u16>,
+{
+    type Item = Result<Payload, Error<E>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let result =
+            self.nrf24l01
+                .snapshot()
+                .and_then(|(status, fifo_status)| match status.rx_pipe() {
+                    Some(pipe) if !fifo_status.rx_empty() => {
+                        let mut data = payload_buffer();
+                        let len = self.nrf24l01.get_data(&mut data)?;
+                        Ok(Some(Payload { pipe, len, data }))
+                    }
+                    _ => Ok(None),
+                });
+        match result {
+            Ok(Some(payload)) => Some(Ok(payload)),
+            Ok(None) => {
+                self.done = true;
+                None
+            }
+            Err(e) => {
+                self.done = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+impl<E, SPI, CSN, CE, DELAY, IRQ> NRF24L01<SPI, CSN, CE, DELAY, IRQ>
+where
+    SPI: blocking::spi::Transfer<u8, Error = E> + blocking::spi::Write<u8, Error = E>,
+    CSN: OutputPin,
+    CE: OutputPin,
+    DELAY: DelayUs<u16>,
+{
+    /// Iterate over the payloads in the RX FIFO until it is empty, each with its own
+    /// (dynamic) width and pipe.
+    ///
+    /// RX_DR is cleared once the last payload has been read, like `get_data` does. Dropping
+    /// the iterator early leaves it set, so the packets still waiting keep the IRQ asserted.
+    /// The iterator ends after the first error.
+    pub fn rx_iter(&mut self) -> RxIter<'_, SPI, CSN, CE, DELAY, IRQ> {
+        RxIter {
+            nrf24l01: self,
+            done: false,
+        }
+    }
+}
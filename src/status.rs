@@ -0,0 +1,85 @@
+//! Decoded STATUS and FIFO_STATUS registers
+
+use crate::{BitMnemonic, Pipe};
+
+/// The STATUS register
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Status(pub u8);
+
+impl Status {
+    /// A packet was received (RX_DR)
+    pub fn data_ready(self) -> bool {
+        self.0 & (1 << BitMnemonic::RX_DR) != 0
+    }
+
+    /// A packet was sent (TX_DS)
+    pub fn data_sent(self) -> bool {
+        self.0 & (1 << BitMnemonic::TX_DS) != 0
+    }
+
+    /// The maximum number of retransmits was reached (MAX_RT)
+    pub fn max_retransmits(self) -> bool {
+        self.0 & (1 << BitMnemonic::MAX_RT) != 0
+    }
+
+    /// The pipe of the packet at the top of the RX FIFO, `None` if it is empty
+    pub fn rx_pipe(self) -> Option<Pipe> {
+        Pipe::from_status(self.0)
+    }
+
+    /// The TX FIFO is full
+    pub fn tx_full(self) -> bool {
+        self.0 & (1 << BitMnemonic::TX_FULL) != 0
+    }
+}
+
+/// The FIFO_STATUS register
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FifoStatus(pub u8);
+
+impl FifoStatus {
+    /// The last TX payload is being reused (TX_REUSE)
+    pub fn tx_reuse(self) -> bool {
+        self.0 & (1 << BitMnemonic::TX_REUSE) != 0
+    }
+
+    /// The TX FIFO is full
+    pub fn tx_full(self) -> bool {
+        self.0 & (1 << BitMnemonic::FIFO_FULL) != 0
+    }
+
+    /// The TX FIFO is empty
+    pub fn tx_empty(self) -> bool {
+        self.0 & (1 << BitMnemonic::TX_EMPTY) != 0
+    }
+
+    /// The RX FIFO is full
+    pub fn rx_full(self) -> bool {
+        self.0 & (1 << BitMnemonic::RX_FULL) != 0
+    }
+
+    /// The RX FIFO is empty
+    pub fn rx_empty(self) -> bool {
+        self.0 & (1 << BitMnemonic::RX_EMPTY) != 0
+    }
+
+    /// How full the RX FIFO is
+    pub fn rx_occupancy(self) -> FifoOccupancy {
+        if self.rx_empty() {
+            FifoOccupancy::Empty
+        } else if self.rx_full() {
+            FifoOccupancy::Full
+        } else {
+            FifoOccupancy::Partial
+        }
+    }
+}
+
+/// Fill level of a three packet FIFO, as far as FIFO_STATUS tells
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FifoOccupancy {
+    Empty,
+    /// One or two packets
+    Partial,
+    Full,
+}
@@ -0,0 +1,102 @@
+//! Datasheet timings and on-air time calculations
+
+use crate::{AddressWidth, CrcMode, DataRate};
+
+/// Power down to standby settle time (Tpd2stby) in microseconds, waited after setting PWR_UP
+/// while the crystal starts up
+pub(crate) const TPD2STBY_US: u16 = 1500;
+/// Standby to active (RX/TX) settle time (Tstby2a) in microseconds, waited after raising CE
+/// while the PLL locks
+pub(crate) const TSTBY2A_US: u16 = 130;
+/// Back-off step between application level retries in microseconds
+pub(crate) const RETRY_BACKOFF_US: u16 = 500;
+
+/// Interval between STATUS polls in timed waits, in microseconds
+pub(crate) const STATUS_POLL_US: u16 = 10;
+
+/// CE high time that triggers a single transmit, the datasheet minimum is 10µs
+pub(crate) const CE_PULSE_US: u16 = 15;
+
+/// Interval between RPD samples in `link_health`, in microseconds
+pub(crate) const RPD_SAMPLE_INTERVAL_US: u16 = 50;
+
+/// Step size of the auto retransmit delay (ARD) in microseconds
+pub(crate) const ARD_STEP_US: u32 = 250;
+
+/// Preamble plus the 9 bit packet control field
+const PACKET_OVERHEAD_BITS: u32 = 8 + 9;
+
+fn packet_bits(payload_len: u8, address_width: AddressWidth, crc: CrcMode) -> u32 {
+    PACKET_OVERHEAD_BITS
+        + 8 * address_width.bytes() as u32
+        + 8 * payload_len as u32
+        + 8 * crc.bytes() as u32
+}
+
+fn bits_to_us(bits: u32, data_rate: DataRate) -> u32 {
+    match data_rate {
+        DataRate::R250Kbps => bits * 4,
+        DataRate::R1Mbps => bits,
+        DataRate::R2Mbps => bits.div_ceil(2),
+    }
+}
+
+/// On-air time in microseconds of one Enhanced ShockBurst packet carrying `payload_len` bytes.
+///
+/// With `auto_ack` this includes the 130µs RX/TX turnaround and the empty ACK packet sent back
+/// by the receiver. The settle time before the transmit starts is not included.
+pub fn airtime_us(
+    payload_len: u8,
+    data_rate: DataRate,
+    address_width: AddressWidth,
+    crc: CrcMode,
+    auto_ack: bool,
+) -> u32 {
+    let mut airtime = bits_to_us(packet_bits(payload_len, address_width, crc), data_rate);
+    if auto_ack {
+        airtime += TSTBY2A_US as u32 + bits_to_us(packet_bits(0, address_width, crc), data_rate);
+    }
+    airtime
+}
+
+/// Shortest time in microseconds a receiver has to stay in RX, counted from raising CE, to
+/// receive one packet carrying `payload_len` bytes: the 130µs settle time plus the packet's
+/// airtime, preamble included.
+///
+/// This assumes the packet starts right as the receiver is ready. Lining up the sender with
+/// the receiver's window, and any clock drift between them, is up to the caller.
+pub fn min_rx_window_us(
+    payload_len: u8,
+    data_rate: DataRate,
+    address_width: AddressWidth,
+    crc: CrcMode,
+) -> u32 {
+    TSTBY2A_US as u32 + airtime_us(payload_len, data_rate, address_width, crc, false)
+}
+
+/// ARD setting for a delay of at least `us`, clamped to the 250 to 4000µs range
+pub(crate) fn ard_steps_for_us(us: u16) -> u8 {
+    ((us as u32).div_ceil(ARD_STEP_US).clamp(1, 16) - 1) as u8
+}
+
+/// Retransmit delay in microseconds of an ARD setting
+pub(crate) fn ard_steps_to_us(steps: u8) -> u16 {
+    ((steps as u32 + 1) * ARD_STEP_US) as u16
+}
+
+/// Smallest ARD setting (in 250µs steps, 0 meaning 250µs) that leaves time for an ACK carrying
+/// `ack_payload_len` bytes to arrive.
+///
+/// The PTX has to wait the RX/TX turnaround plus the ACK packet's airtime before it may
+/// retransmit. Assumes the worst case of 5 byte addresses and a 2 byte CRC.
+pub(crate) fn min_ard_steps(ack_payload_len: u8, data_rate: DataRate) -> u8 {
+    let wait = TSTBY2A_US as u32
+        + airtime_us(
+            ack_payload_len,
+            data_rate,
+            AddressWidth::Five,
+            CrcMode::TwoBytes,
+            false,
+        );
+    ard_steps_for_us(wait.min(u16::MAX as u32) as u16)
+}